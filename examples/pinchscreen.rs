@@ -76,8 +76,8 @@ impl Pinchscreen {
 	}
 }
 impl RootHandler for Pinchscreen {
-	fn frame(&mut self, _info: FrameInfo) {
-		self.hover_plane.update();
+	fn frame(&mut self, info: FrameInfo) {
+		self.hover_plane.update(&info);
 		if self.hover_plane.interact_status().actor_started() {
 			self.text.set_text("Pressed").unwrap();
 		}
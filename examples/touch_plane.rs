@@ -10,7 +10,11 @@ use stardust_xr_fusion::{
 	root::{ClientState, FrameInfo, RootAspect, RootHandler},
 	spatial::{Spatial, Transform},
 };
-use stardust_xr_molecules::{lines::LineExt, touch_plane::TouchPlane, DebugSettings, VisualDebug};
+use stardust_xr_molecules::{
+	lines::LineExt,
+	touch_plane::{TouchPlane, TouchPlaneSettings},
+	DebugSettings, VisualDebug,
+};
 use std::f32::consts::PI;
 use tracing_subscriber::EnvFilter;
 
@@ -51,6 +55,7 @@ impl TouchPlaneDemo {
 			0.01,
 			-0.15..0.15,
 			0.1..-0.1,
+			TouchPlaneSettings::default(),
 		)?;
 		touch_plane.set_debug(Some(DebugSettings::default()));
 
@@ -1,6 +1,6 @@
 use crate::{
 	lines::{circle, rounded_rectangle, LineExt},
-	touch_plane::TouchPlane,
+	touch_plane::{TouchPlane, TouchPlaneSettings},
 	VisualDebug,
 };
 use glam::{vec3, Mat4};
@@ -66,6 +66,7 @@ impl Button {
 			0.015,
 			-half_size_x..half_size_x,
 			half_size_y..-half_size_y,
+			TouchPlaneSettings::default(),
 		)?;
 
 		Ok(Button {
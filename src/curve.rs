@@ -0,0 +1,156 @@
+use glam::{vec3, Vec3};
+use lerp::Lerp;
+use stardust_xr_fusion::{
+	drawable::{Line, LinePoint},
+	fields::Shape,
+	spatial::Transform,
+};
+
+/// A section of a vertical cylinder that a `TouchPlane` or `HoverPlane` can be bent along.
+///
+/// The surface passes through the plane's origin and curves towards +z,
+/// so the cylinder's axis is `radius` in front of the plane, e.g. where the user's head is.
+///
+/// Fields can't follow a curved shell, so the plane's field is a box around the whole arc.
+/// That box reaches `radius * (1 - cos(arc / 2))` in front of the middle of the surface
+/// (about 30% of the radius at 90°, all of it at 180°), and anything inside it counts as inside the field,
+/// which affects input ordering and capture for everything in front of the plane.
+/// Keep the arc small, or the radius large, if that space is used for other things.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CylinderCurve {
+	pub radius: f32,
+	/// How much of the cylinder the surface wraps around, in radians.
+	pub arc: f32,
+}
+impl CylinderCurve {
+	/// Length of the surface along the curve, which is what `x_range` maps onto.
+	pub fn width(&self) -> f32 {
+		self.radius * self.arc
+	}
+
+	/// Unroll a point around the cylinder onto the flat plane.
+	///
+	/// x becomes the distance along the curve and z the distance in front of the surface.
+	pub fn flatten(&self, point: impl Into<Vec3>) -> Vec3 {
+		let point = point.into();
+		let from_axis = vec3(point.x, 0.0, point.z - self.radius);
+		let angle = from_axis.x.atan2(-from_axis.z);
+		vec3(
+			angle * self.radius,
+			point.y,
+			self.radius - from_axis.length(),
+		)
+	}
+	/// Bend a point on the flat plane around the cylinder, the inverse of `flatten`.
+	pub fn curve(&self, point: impl Into<Vec3>) -> Vec3 {
+		let point = point.into();
+		let (sin, cos) = (point.x / self.radius).sin_cos();
+		let distance = self.radius - point.z;
+		vec3(distance * sin, point.y, self.radius - distance * cos)
+	}
+
+	/// Where a ray first hits the curved surface, if it hits inside the arc.
+	pub fn ray_intersect(
+		&self,
+		origin: impl Into<Vec3>,
+		direction: impl Into<Vec3>,
+	) -> Option<Vec3> {
+		let origin = origin.into();
+		let direction = direction.into();
+		// only x and z matter since the cylinder is vertical
+		let o = vec3(origin.x, 0.0, origin.z - self.radius);
+		let d = vec3(direction.x, 0.0, direction.z);
+		let a = d.length_squared();
+		if a < f32::EPSILON {
+			return None;
+		}
+		let b = 2.0 * o.dot(d);
+		let c = o.length_squared() - self.radius * self.radius;
+		let discriminant = b * b - 4.0 * a * c;
+		if discriminant < 0.0 {
+			return None;
+		}
+		let root = discriminant.sqrt();
+		[(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+			.into_iter()
+			.filter(|t| *t >= 0.0)
+			.map(|t| origin + direction * t)
+			.find(|p| self.flatten(*p).x.abs() * 2.0 <= self.width())
+	}
+
+	/// The field a `height` tall surface with `thickness` behind it needs to cover, a box around the whole arc.
+	pub(crate) fn field(&self, height: f32, thickness: f32) -> (Transform, Shape) {
+		let half_width = self.width() * 0.5;
+		let (min, max) = (0..=Self::SEGMENTS)
+			.map(|i| (i as f32 / Self::SEGMENTS as f32 - 0.5) * 2.0 * half_width)
+			.flat_map(|x| [self.curve([x, 0.0, 0.0]), self.curve([x, 0.0, -thickness])])
+			.fold((Vec3::MAX, Vec3::MIN), |(min, max), p| {
+				(min.min(p), max.max(p))
+			});
+		let mut size = max - min;
+		size.y = height;
+		(
+			Transform::from_translation((min + max) * 0.5),
+			Shape::Box(size.into()),
+		)
+	}
+
+	/// Bend a line drawn on the flat plane around the cylinder, adding points so it follows the curve.
+	pub(crate) fn curve_line(&self, line: Line) -> Line {
+		let max_step = self.width() / Self::SEGMENTS as f32;
+		let next_points = line.points.iter().cycle().skip(1);
+		let mut points = Vec::with_capacity(line.points.len());
+		for (index, (point, next)) in line.points.iter().zip(next_points).enumerate() {
+			points.push(LinePoint {
+				point: self.curve(point.point).into(),
+				..point.clone()
+			});
+			if !line.cyclic && index == line.points.len() - 1 {
+				break;
+			}
+			let steps = ((next.point.x - point.point.x).abs() / max_step).ceil() as usize;
+			for step in 1..steps {
+				let t = step as f32 / steps as f32;
+				let flat = Vec3::from(point.point).lerp(next.point.into(), t);
+				points.push(LinePoint {
+					point: self.curve(flat).into(),
+					thickness: point.thickness.lerp_bounded(next.thickness, t),
+					color: point.color.lerp_bounded(next.color, t),
+				});
+			}
+		}
+		Line {
+			points,
+			cyclic: line.cyclic,
+		}
+	}
+	const SEGMENTS: usize = 32;
+}
+
+#[test]
+fn cylinder_curve_round_trip() {
+	let curve = CylinderCurve {
+		radius: 1.0,
+		arc: std::f32::consts::FRAC_PI_2,
+	};
+	for point in [
+		vec3(0.0, 0.0, 0.0),
+		vec3(0.5, 0.2, 0.1),
+		vec3(-0.7, -0.3, -0.05),
+	] {
+		assert!(curve.curve(curve.flatten(point)).distance(point) < 0.0001);
+		assert!(curve.flatten(curve.curve(point)).distance(point) < 0.0001);
+	}
+
+	// looking straight ahead from the axis hits the middle of the surface
+	let hit = curve
+		.ray_intersect(vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, -1.0))
+		.unwrap();
+	assert!(hit.distance(Vec3::ZERO) < 0.0001);
+	// looking backwards from the axis misses the arc
+	assert!(curve
+		.ray_intersect(vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, 1.0))
+		.is_none());
+	// parallel to the axis never hits
+	assert!(curve.ray_intersect(Vec3::ZERO, Vec3::Y).is_none());
+}
@@ -0,0 +1,253 @@
+use crate::{grabbable::PoseAnimation, GrabbablePose};
+use glam::Quat;
+use rustc_hash::{FxHashMap, FxHashSet};
+use stardust_xr_fusion::{
+	core::values::Vector3,
+	fields::Field,
+	node::{NodeError, NodeType},
+	root::FrameInfo,
+	spatial::{
+		Spatial, SpatialAspect, SpatialRef, SpatialRefAspect, Transform, Zone, ZoneAspect,
+		ZoneHandler,
+	},
+	HandlerWrapper,
+};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+#[derive(Debug, Clone)]
+pub struct DockSettings {
+	/// Where docked items get snapped to, relative to the dock.
+	pub slots: Vec<Vector3<f32>>,
+	/// How long snapping into a slot takes, in seconds.
+	pub snap_duration: f32,
+}
+impl Default for DockSettings {
+	fn default() -> Self {
+		Self {
+			slots: vec![[0.0; 3].into()],
+			snap_duration: 0.2,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockEvent {
+	/// The spatial with this id finished snapping into a slot.
+	Docked { id: u64, slot: usize },
+	/// The spatial with this id was taken out of its slot, e.g. by being grabbed.
+	Undocked { id: u64, slot: usize },
+}
+
+/// A region that pulls in released `Grabbable`s (or anything zoneable) and snaps them into slots.
+///
+/// Grabbables are only zoneable once they've been let go of and stopped moving,
+/// so anything that comes to rest inside the field gets docked.
+pub struct Dock {
+	root: Spatial,
+	zone: HandlerWrapper<Zone, DockZoneHandler>,
+	settings: DockSettings,
+
+	slots: Vec<Option<DockedItem>>,
+	capture_requested: FxHashSet<u64>,
+	start_transform_tx: mpsc::Sender<(u64, Transform)>,
+	start_transform_rx: mpsc::Receiver<(u64, Transform)>,
+
+	events: Vec<DockEvent>,
+}
+impl Dock {
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		field: &Field,
+		settings: DockSettings,
+	) -> Result<Self, NodeError> {
+		let root = Spatial::create(parent, transform, false)?;
+		let zone =
+			Zone::create(&root, Transform::none(), field)?.wrap(DockZoneHandler::default())?;
+		let (start_transform_tx, start_transform_rx) = mpsc::channel(8);
+
+		Ok(Dock {
+			root,
+			zone,
+			slots: settings.slots.iter().map(|_| None).collect(),
+			settings,
+
+			capture_requested: FxHashSet::default(),
+			start_transform_tx,
+			start_transform_rx,
+
+			events: Vec::new(),
+		})
+	}
+
+	/// Update the state of this dock. Run once every frame.
+	pub fn update(&mut self, info: &FrameInfo) -> Result<(), NodeError> {
+		self.events.clear();
+		self.zone.node().update()?;
+
+		let (entered, captured, released) = {
+			let mut handler = self.zone.lock_wrapped();
+			self.capture_requested
+				.retain(|id| handler.entered.contains_key(id));
+			(
+				handler
+					.entered
+					.iter()
+					.map(|(id, spatial)| (*id, spatial.alias()))
+					.collect::<Vec<_>>(),
+				std::mem::take(&mut handler.captured),
+				std::mem::take(&mut handler.released),
+			)
+		};
+
+		for id in released {
+			let Some(slot) = self.slot_of(id) else {
+				continue;
+			};
+			self.slots[slot].take();
+			debug!(id, slot, "Undocked");
+			self.events.push(DockEvent::Undocked { id, slot });
+		}
+
+		for spatial in captured {
+			// it may have been destroyed since, which shouldn't stop the rest from docking
+			let Ok(id) = spatial.node().get_id() else {
+				continue;
+			};
+			self.capture_requested.remove(&id);
+			let Some(slot) = self.free_slot() else {
+				// filled up since we asked to capture it
+				self.zone.node().release(&spatial)?;
+				continue;
+			};
+			spatial.set_spatial_parent_in_place(&self.root)?;
+
+			// find out where it is so it can be animated into the slot from there
+			let root = self.root.alias();
+			let spatial_alias = spatial.alias();
+			let start_transform_tx = self.start_transform_tx.clone();
+			tokio::task::spawn(async move {
+				if let Ok(transform) = spatial_alias.get_transform(&root).await {
+					let _ = start_transform_tx.send((id, transform)).await;
+				}
+			});
+			self.slots[slot].replace(DockedItem {
+				id,
+				spatial,
+				animation: None,
+			});
+		}
+
+		// only ask to capture as many things as there's room for
+		let free_slots = self.slots.iter().filter(|s| s.is_none()).count();
+		for (id, spatial) in entered {
+			if free_slots <= self.capture_requested.len() {
+				break;
+			}
+			if self.capture_requested.contains(&id) || self.slot_of(id).is_some() {
+				continue;
+			}
+			self.zone.node().capture(&spatial)?;
+			self.capture_requested.insert(id);
+		}
+
+		while let Ok((id, transform)) = self.start_transform_rx.try_recv() {
+			let Some(slot) = self.slot_of(id) else {
+				continue;
+			};
+			let from = GrabbablePose::from(transform);
+			let to = GrabbablePose::new(self.settings.slots[slot], Quat::IDENTITY)
+				.with_scale(from.scale);
+			if let Some(item) = &mut self.slots[slot] {
+				item.animation.replace(PoseAnimation::new(from, to));
+			}
+		}
+
+		let progress = info.delta / self.settings.snap_duration;
+		for (slot, item) in self.slots.iter_mut().enumerate() {
+			let Some(item) = item else {
+				continue;
+			};
+			let Some(animation) = &mut item.animation else {
+				continue;
+			};
+			if animation.finished() {
+				continue;
+			}
+			let pose = animation.advance(progress);
+			item.spatial.set_local_transform(pose.into())?;
+			if animation.finished() {
+				debug!(id = item.id, slot, "Docked");
+				self.events.push(DockEvent::Docked { id: item.id, slot });
+			}
+		}
+
+		Ok(())
+	}
+
+	fn slot_of(&self, id: u64) -> Option<usize> {
+		self.slots
+			.iter()
+			.position(|s| s.as_ref().is_some_and(|i| i.id == id))
+	}
+	fn free_slot(&self) -> Option<usize> {
+		self.slots.iter().position(Option::is_none)
+	}
+
+	/// Everything that happened during the last update.
+	pub fn events(&self) -> &[DockEvent] {
+		&self.events
+	}
+	/// The spatial docked in each slot, if any.
+	pub fn docked(&self) -> impl Iterator<Item = Option<&Spatial>> {
+		self.slots.iter().map(|s| s.as_ref().map(|i| &i.spatial))
+	}
+	/// Let go of whatever is in this slot.
+	pub fn undock(&mut self, slot: usize) -> Result<(), NodeError> {
+		let Some(item) = self.slots.get_mut(slot).and_then(Option::take) else {
+			return Ok(());
+		};
+		self.zone.node().release(&item.spatial)?;
+		self.events.push(DockEvent::Undocked { id: item.id, slot });
+		Ok(())
+	}
+
+	pub fn root(&self) -> &Spatial {
+		&self.root
+	}
+	pub fn zone(&self) -> &Zone {
+		self.zone.node()
+	}
+}
+
+struct DockedItem {
+	id: u64,
+	spatial: Spatial,
+	/// None until we know where it started from
+	animation: Option<PoseAnimation>,
+}
+
+#[derive(Default)]
+struct DockZoneHandler {
+	entered: FxHashMap<u64, SpatialRef>,
+	captured: Vec<Spatial>,
+	released: Vec<u64>,
+}
+impl ZoneHandler for DockZoneHandler {
+	fn enter(&mut self, spatial: SpatialRef) {
+		let Ok(id) = spatial.node().get_id() else {
+			return;
+		};
+		self.entered.insert(id, spatial);
+	}
+	fn capture(&mut self, spatial: Spatial) {
+		self.captured.push(spatial);
+	}
+	fn release(&mut self, id: u64) {
+		self.released.push(id);
+	}
+	fn leave(&mut self, id: u64) {
+		self.entered.remove(&id);
+	}
+}
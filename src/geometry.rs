@@ -0,0 +1,202 @@
+//! Intersection and distance functions shared by the widgets.
+//!
+//! Ray functions return how far along the ray the hit is, in multiples of `direction`,
+//! so `ray.at(t)` gives the point. Hits behind the ray's origin don't count.
+
+use glam::Vec3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+	pub origin: Vec3,
+	/// Doesn't need to be normalized.
+	pub direction: Vec3,
+}
+impl Ray {
+	pub fn new(origin: impl Into<Vec3>, direction: impl Into<Vec3>) -> Self {
+		Ray {
+			origin: origin.into(),
+			direction: direction.into(),
+		}
+	}
+	pub fn at(&self, t: f32) -> Vec3 {
+		self.origin + self.direction * t
+	}
+}
+
+/// Where a ray hits an infinite plane. None if it's parallel to the plane or pointing away from it.
+pub fn ray_plane(ray: Ray, plane_point: Vec3, plane_normal: Vec3) -> Option<f32> {
+	let denom = plane_normal.dot(ray.direction);
+	if denom.abs() < f32::EPSILON {
+		return None;
+	}
+	let t = (plane_point - ray.origin).dot(plane_normal) / denom;
+	(t >= 0.0).then_some(t)
+}
+
+/// Where a ray enters an axis aligned box, 0 if it starts inside.
+pub fn ray_box(ray: Ray, center: Vec3, size: Vec3) -> Option<f32> {
+	let min = center - size * 0.5;
+	let max = center + size * 0.5;
+	let mut t_enter = f32::NEG_INFINITY;
+	let mut t_exit = f32::INFINITY;
+	for axis in 0..3 {
+		let (origin, direction) = (ray.origin[axis], ray.direction[axis]);
+		if direction.abs() < f32::EPSILON {
+			// parallel to this slab, so it has to already be between its sides
+			if origin < min[axis] || origin > max[axis] {
+				return None;
+			}
+			continue;
+		}
+		let t_min = (min[axis] - origin) / direction;
+		let t_max = (max[axis] - origin) / direction;
+		t_enter = t_enter.max(t_min.min(t_max));
+		t_exit = t_exit.min(t_min.max(t_max));
+	}
+	if t_enter > t_exit || t_exit < 0.0 {
+		return None;
+	}
+	Some(t_enter.max(0.0))
+}
+
+/// Where a ray enters a sphere, 0 if it starts inside.
+pub fn ray_sphere(ray: Ray, center: Vec3, radius: f32) -> Option<f32> {
+	let a = ray.direction.length_squared();
+	if a < f32::EPSILON {
+		return None;
+	}
+	let offset = ray.origin - center;
+	let c = offset.length_squared() - radius * radius;
+	if c <= 0.0 {
+		return Some(0.0);
+	}
+	let b = offset.dot(ray.direction);
+	let discriminant = b * b - a * c;
+	if discriminant < 0.0 {
+		return None;
+	}
+	let t = (-b - discriminant.sqrt()) / a;
+	(t >= 0.0).then_some(t)
+}
+
+/// The point on the segment from `a` to `b` closest to `point`.
+pub fn closest_point_on_segment(a: Vec3, b: Vec3, point: Vec3) -> Vec3 {
+	let ab = b - a;
+	let length_squared = ab.length_squared();
+	if length_squared < f32::EPSILON {
+		return a;
+	}
+	let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+	a + ab * t
+}
+
+#[cfg(test)]
+fn approx(a: f32, b: f32) -> bool {
+	(a - b).abs() < 0.0001
+}
+
+#[test]
+fn ray_plane_hits() {
+	use glam::vec3;
+	let ray = Ray::new(vec3(0.0, 0.0, 2.0), vec3(0.0, 0.0, -1.0));
+	let t = ray_plane(ray, Vec3::ZERO, Vec3::Z).unwrap();
+	assert!(approx(t, 2.0));
+	assert!(ray.at(t).abs_diff_eq(Vec3::ZERO, 0.0001));
+
+	// unnormalized direction scales t
+	let ray = Ray::new(vec3(1.0, 1.0, 2.0), vec3(0.0, 0.0, -2.0));
+	let t = ray_plane(ray, Vec3::ZERO, Vec3::Z).unwrap();
+	assert!(approx(t, 1.0));
+	assert!(ray.at(t).abs_diff_eq(vec3(1.0, 1.0, 0.0), 0.0001));
+
+	// hitting the back of the plane still counts
+	let ray = Ray::new(vec3(0.0, 0.0, -1.0), vec3(0.5, 0.0, 1.0));
+	assert!(ray.at(ray_plane(ray, Vec3::ZERO, Vec3::Z).unwrap()).z.abs() < 0.0001);
+}
+
+#[test]
+fn ray_plane_misses() {
+	use glam::vec3;
+	// parallel, both off and on the plane
+	let ray = Ray::new(vec3(0.0, 0.0, 1.0), Vec3::X);
+	assert_eq!(ray_plane(ray, Vec3::ZERO, Vec3::Z), None);
+	let ray = Ray::new(Vec3::ZERO, Vec3::X);
+	assert_eq!(ray_plane(ray, Vec3::ZERO, Vec3::Z), None);
+	// pointing away
+	let ray = Ray::new(vec3(0.0, 0.0, 1.0), Vec3::Z);
+	assert_eq!(ray_plane(ray, Vec3::ZERO, Vec3::Z), None);
+	// zero direction
+	let ray = Ray::new(vec3(0.0, 0.0, 1.0), Vec3::ZERO);
+	assert_eq!(ray_plane(ray, Vec3::ZERO, Vec3::Z), None);
+}
+
+#[test]
+fn ray_box_hits_and_misses() {
+	use glam::vec3;
+	let size = Vec3::splat(2.0);
+	let ray = Ray::new(vec3(-5.0, 0.0, 0.0), Vec3::X);
+	assert!(approx(ray_box(ray, Vec3::ZERO, size).unwrap(), 4.0));
+	// offset center
+	let ray = Ray::new(vec3(0.0, 5.0, 3.0), Vec3::NEG_Y);
+	assert!(approx(
+		ray_box(ray, vec3(0.0, 0.0, 3.0), size).unwrap(),
+		4.0
+	));
+	// diagonal
+	let ray = Ray::new(vec3(-3.0, -3.0, 0.0), vec3(1.0, 1.0, 0.0));
+	assert!(approx(ray_box(ray, Vec3::ZERO, size).unwrap(), 2.0));
+	// inside
+	let ray = Ray::new(Vec3::ZERO, Vec3::X);
+	assert_eq!(ray_box(ray, Vec3::ZERO, size), Some(0.0));
+
+	// behind
+	let ray = Ray::new(vec3(5.0, 0.0, 0.0), Vec3::X);
+	assert_eq!(ray_box(ray, Vec3::ZERO, size), None);
+	// passes beside
+	let ray = Ray::new(vec3(-5.0, 2.0, 0.0), Vec3::X);
+	assert_eq!(ray_box(ray, Vec3::ZERO, size), None);
+	// parallel to a slab but outside it
+	let ray = Ray::new(vec3(-5.0, 0.0, 1.5), vec3(1.0, 0.1, 0.0));
+	assert_eq!(ray_box(ray, Vec3::ZERO, size), None);
+}
+
+#[test]
+fn ray_sphere_hits_and_misses() {
+	use glam::vec3;
+	let ray = Ray::new(vec3(0.0, 0.0, 5.0), Vec3::NEG_Z);
+	assert!(approx(ray_sphere(ray, Vec3::ZERO, 1.0).unwrap(), 4.0));
+	// unnormalized direction
+	let ray = Ray::new(vec3(0.0, 0.0, 5.0), vec3(0.0, 0.0, -2.0));
+	assert!(approx(ray_sphere(ray, Vec3::ZERO, 1.0).unwrap(), 2.0));
+	// grazing
+	let ray = Ray::new(vec3(1.0, 0.0, 5.0), Vec3::NEG_Z);
+	assert!(approx(ray_sphere(ray, Vec3::ZERO, 1.0).unwrap(), 5.0));
+	// inside
+	let ray = Ray::new(vec3(0.2, 0.0, 0.0), Vec3::X);
+	assert_eq!(ray_sphere(ray, Vec3::ZERO, 1.0), Some(0.0));
+
+	// beside
+	let ray = Ray::new(vec3(1.5, 0.0, 5.0), Vec3::NEG_Z);
+	assert_eq!(ray_sphere(ray, Vec3::ZERO, 1.0), None);
+	// behind
+	let ray = Ray::new(vec3(0.0, 0.0, 5.0), Vec3::Z);
+	assert_eq!(ray_sphere(ray, Vec3::ZERO, 1.0), None);
+	// zero direction
+	let ray = Ray::new(vec3(0.0, 0.0, 5.0), Vec3::ZERO);
+	assert_eq!(ray_sphere(ray, Vec3::ZERO, 1.0), None);
+}
+
+#[test]
+fn closest_point_on_segment_clamps() {
+	use glam::vec3;
+	let a = vec3(-1.0, 0.0, 0.0);
+	let b = vec3(1.0, 0.0, 0.0);
+	assert_eq!(
+		closest_point_on_segment(a, b, vec3(0.5, 2.0, 0.0)),
+		vec3(0.5, 0.0, 0.0)
+	);
+	assert_eq!(closest_point_on_segment(a, b, vec3(-3.0, 1.0, 0.0)), a);
+	assert_eq!(closest_point_on_segment(a, b, vec3(3.0, -1.0, 1.0)), b);
+	// degenerate segment
+	assert_eq!(closest_point_on_segment(a, a, vec3(3.0, 1.0, 0.0)), a);
+}
@@ -0,0 +1,106 @@
+use crate::touch_plane::{ContactId, TouchPlane};
+use glam::Vec2;
+use rustc_hash::FxHashMap;
+use stardust_xr_fusion::core::values::Vector2;
+use std::f32::consts::{PI, TAU};
+
+/// How two or more touches moved relative to each other since the last update.
+///
+/// Everything is in the touch plane's `x_range`/`y_range` coordinates,
+/// rotation is counter-clockwise in radians when y goes up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureDelta {
+	/// Where the touches are centered.
+	pub center: Vector2<f32>,
+	/// How far the center moved.
+	pub pan: Vector2<f32>,
+	/// How much the touches spread apart, 1.0 means no change.
+	pub zoom: f32,
+	/// How much the touches rotated around the center.
+	pub rotation: f32,
+}
+
+/// Turns the contacts on a `TouchPlane` into pan, pinch-zoom and rotate gestures for e.g. maps and image viewers.
+///
+/// With more than one finger enabled in the touch plane's settings, a single hand can make gestures too.
+#[derive(Debug, Default)]
+pub struct MultiTouchGesture {
+	previous_points: FxHashMap<ContactId, Vec2>,
+}
+impl MultiTouchGesture {
+	/// Run once every frame after updating the touch plane.
+	///
+	/// Returns None if fewer than 2 contacts have been touching since the last update.
+	pub fn update(&mut self, touch_plane: &TouchPlane) -> Option<GestureDelta> {
+		let current_points = touch_plane
+			.current_contacts()
+			.map(|c| (c.id, Vec2::from(c.point)))
+			.collect::<FxHashMap<_, _>>();
+
+		// only contacts that were touching last time too can tell us how they moved
+		let (previous, current): (Vec<Vec2>, Vec<Vec2>) = current_points
+			.iter()
+			.filter_map(|(id, point)| Some((*self.previous_points.get(id)?, *point)))
+			.unzip();
+		self.previous_points = current_points;
+
+		gesture_delta(&previous, &current)
+	}
+}
+
+/// `previous` and `current` are the same touches in the same order
+fn gesture_delta(previous: &[Vec2], current: &[Vec2]) -> Option<GestureDelta> {
+	if current.len() < 2 || previous.len() != current.len() {
+		return None;
+	}
+	let count = current.len() as f32;
+	let previous_center = previous.iter().sum::<Vec2>() / count;
+	let current_center = current.iter().sum::<Vec2>() / count;
+
+	let mut previous_spread = 0.0;
+	let mut current_spread = 0.0;
+	let mut rotation = 0.0;
+	for (previous, current) in previous.iter().zip(current) {
+		let previous_offset = *previous - previous_center;
+		let current_offset = *current - current_center;
+		previous_spread += previous_offset.length();
+		current_spread += current_offset.length();
+
+		let angle =
+			current_offset.y.atan2(current_offset.x) - previous_offset.y.atan2(previous_offset.x);
+		// wrap so crossing the -x axis doesn't count as a full turn
+		rotation += (angle + PI).rem_euclid(TAU) - PI;
+	}
+
+	Some(GestureDelta {
+		center: current_center.into(),
+		pan: (current_center - previous_center).into(),
+		zoom: if previous_spread > f32::EPSILON {
+			current_spread / previous_spread
+		} else {
+			1.0
+		},
+		rotation: rotation / count,
+	})
+}
+
+#[test]
+fn gesture_delta_pan_zoom_rotate() {
+	use glam::vec2;
+	let previous = [vec2(-1.0, 0.0), vec2(1.0, 0.0)];
+
+	let panned = gesture_delta(&previous, &[vec2(0.0, 1.0), vec2(2.0, 1.0)]).unwrap();
+	assert_eq!(Vec2::from(panned.pan), vec2(1.0, 1.0));
+	assert_eq!(panned.zoom, 1.0);
+	assert_eq!(panned.rotation, 0.0);
+
+	let zoomed = gesture_delta(&previous, &[vec2(-2.0, 0.0), vec2(2.0, 0.0)]).unwrap();
+	assert_eq!(Vec2::from(zoomed.pan), Vec2::ZERO);
+	assert_eq!(zoomed.zoom, 2.0);
+
+	let rotated = gesture_delta(&previous, &[vec2(0.0, -1.0), vec2(0.0, 1.0)]).unwrap();
+	assert!((rotated.rotation - PI / 2.0).abs() < 0.0001);
+	assert!((rotated.zoom - 1.0).abs() < 0.0001);
+
+	assert!(gesture_delta(&previous[..1], &[vec2(0.0, 0.0)]).is_none());
+}
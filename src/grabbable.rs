@@ -1,14 +1,16 @@
 use crate::{
 	input_action::{InputQueue, InputQueueable, SingleAction},
-	lines::{axes, bounding_box, LineExt},
-	VisualDebug,
+	lines::{axes, bounding_box, circle, line_from_points, LineExt},
+	DebugSettings, VisualDebug,
 };
-use glam::{vec3, Quat, Vec3};
+use glam::{vec3, Mat4, Quat, Vec3};
+use lerp::Lerp;
+use serde::{Deserialize, Serialize};
 use stardust_xr_fusion::{
-	core::values::Vector3,
+	core::values::{color::rgba_linear, Vector3},
 	drawable::{Lines, LinesAspect},
 	fields::{Field, FieldRefAspect},
-	input::{InputData, InputDataType, InputHandler},
+	input::{Hand, InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeType},
 	root::FrameInfo,
 	spatial::{Spatial, SpatialAspect, SpatialRefAspect, Transform},
@@ -23,6 +25,21 @@ fn swing_direction(direction: Vec3) -> Quat {
 	Quat::from_rotation_y(-yaw - PI / 2.0) * Quat::from_rotation_x(pitch)
 }
 
+/// Critically damped spring towards `target`, see Game Programming Gems 4 chapter 1.10.
+fn smooth_damp(current: Vec3, target: Vec3, velocity: &mut Vec3, time: f32, delta: f32) -> Vec3 {
+	if time <= 0.0 {
+		*velocity = Vec3::ZERO;
+		return target;
+	}
+	let omega = 2.0 / time;
+	let x = omega * delta;
+	let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+	let change = current - target;
+	let temp = (*velocity + omega * change) * delta;
+	*velocity = (*velocity - omega * temp) * exp;
+	target + (change + temp) * exp
+}
+
 /// How should the grabbable interact with pointers?
 #[derive(Debug, Clone, Copy)]
 pub enum PointerMode {
@@ -32,6 +49,10 @@ pub enum PointerMode {
 	Align,
 	/// The grabbable never rotates, only moves
 	Move,
+	/// The grabbable only rotates around the vertical axis to keep facing the pointer origin
+	Billboard,
+	/// Like `Align`, but the point where the pointer hit stays under the pointer instead of the grabbable being re-centered on it
+	HitOffset,
 }
 
 /// Linear drag is in m/s, angular drag is in rad/s.
@@ -43,6 +64,157 @@ pub struct MomentumSettings {
 	pub threshold: f32,
 }
 
+/// Snapshot of where a grabbable's content is, relative to the client root.
+///
+/// Serializable so it can be stored in `ClientState` and restored with [`Grabbable::set_pose`] on launch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GrabbablePose {
+	pub position: [f32; 3],
+	/// Quaternion in xyzw order
+	pub rotation: [f32; 4],
+	/// Uniform scale from [`ScaleSettings`], 1.0 if the content was never scaled.
+	#[serde(default = "default_scale")]
+	pub scale: f32,
+}
+fn default_scale() -> f32 {
+	1.0
+}
+impl GrabbablePose {
+	pub fn new(position: impl Into<Vec3>, rotation: impl Into<Quat>) -> Self {
+		GrabbablePose {
+			position: position.into().to_array(),
+			rotation: rotation.into().to_array(),
+			scale: 1.0,
+		}
+	}
+	pub fn with_scale(self, scale: f32) -> Self {
+		GrabbablePose { scale, ..self }
+	}
+	pub fn position(&self) -> Vec3 {
+		self.position.into()
+	}
+	pub fn rotation(&self) -> Quat {
+		Quat::from_array(self.rotation)
+	}
+}
+impl Default for GrabbablePose {
+	fn default() -> Self {
+		GrabbablePose::new(Vec3::ZERO, Quat::IDENTITY)
+	}
+}
+impl From<GrabbablePose> for Transform {
+	fn from(pose: GrabbablePose) -> Self {
+		Transform::from_translation_rotation_scale(
+			pose.position(),
+			pose.rotation(),
+			Vec3::splat(pose.scale),
+		)
+	}
+}
+impl From<Transform> for GrabbablePose {
+	fn from(transform: Transform) -> Self {
+		GrabbablePose::new(
+			transform.translation.map(Vec3::from).unwrap_or_default(),
+			transform.rotation.map(Quat::from).unwrap_or_default(),
+		)
+		// grabbables only ever scale uniformly
+		.with_scale(transform.scale.map_or(1.0, |scale| scale.x))
+	}
+}
+
+/// Something that happened to a grabbable during the last update.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrabbableEvent {
+	/// Something started grabbing it.
+	PickedUp,
+	/// Something else grabbed it while it was already being held.
+	HandedOff,
+	/// It was let go of, with the velocity it was thrown at if it was thrown.
+	Released { velocity: Option<Vector3<f32>> },
+	/// It stopped moving and came to rest at this pose, relative to the client root.
+	SettledAt { pose: GrabbablePose },
+	/// It stopped moving and can be captured by zones again.
+	BecameZoneable,
+	/// A zone captured it after it became zoneable. Only reported with [`GrabbableSettings::watch_zones`] on.
+	///
+	/// The server doesn't tell clients about zone captures, so this is noticed by polling the content's pose
+	/// while it's resting and seeing it moved by something else. That costs a server round trip 4 times a second
+	/// for as long as the content rests, and moving the content yourself through
+	/// [`Grabbable::content_parent`] gets reported as entering a zone too (use [`Grabbable::set_pose`] instead).
+	EnteredZone,
+}
+
+/// Settings for the move history used by [`Grabbable::undo`] and [`Grabbable::redo`].
+#[derive(Debug, Clone, Copy)]
+pub struct MoveHistorySettings {
+	/// Maximum amount of moves that can be undone.
+	pub max_entries: usize,
+	/// How long it takes to animate back to a previous pose, in seconds.
+	pub animation_duration: f32,
+}
+impl Default for MoveHistorySettings {
+	fn default() -> Self {
+		Self {
+			max_entries: 32,
+			animation_duration: 0.25,
+		}
+	}
+}
+
+/// How grabbed content can be scaled, the scale is uniform and relative to when the grabbable was created.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaleSettings {
+	/// Smallest the content can be scaled to.
+	pub min: f32,
+	/// Largest the content can be scaled to.
+	pub max: f32,
+	/// How much each unit of pointer scroll scales by, replaces pushing/pulling the content with scroll.
+	pub scroll_speed: f32,
+	/// Should pinching the content with a second hand while grabbing it scale it as the hands move apart?
+	///
+	/// Grabs can't be handed off while this is on, since the second hand scales instead of taking over.
+	pub two_handed: bool,
+}
+impl Default for ScaleSettings {
+	fn default() -> Self {
+		Self {
+			min: 0.1,
+			max: 10.0,
+			scroll_speed: 0.1,
+			two_handed: true,
+		}
+	}
+}
+
+/// Smooths out hand jitter by having the content chase the grab point instead of being locked to it.
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothingSettings {
+	/// Roughly how long the position takes to catch up to the grab point, in seconds. 0 means no smoothing.
+	pub position_time: f32,
+	/// Roughly how long the rotation takes to catch up to the grab point, in seconds. 0 means no smoothing.
+	pub rotation_time: f32,
+	/// Lazy follow: the content stays put until the grab point is this far away (in meters),
+	/// then catches up completely. Useful for large panels.
+	pub dead_zone: Option<f32>,
+}
+impl Default for SmoothingSettings {
+	fn default() -> Self {
+		Self {
+			position_time: 0.05,
+			rotation_time: 0.05,
+			dead_zone: None,
+		}
+	}
+}
+
+/// Keeps thrown content inside a field, checked against the content's origin. See [`Grabbable::set_bounds`].
+pub struct MomentumBounds {
+	/// Field the content has to stay inside of.
+	pub field: Field,
+	/// How much speed is kept when bouncing off the edge of the field, None means stop at the edge instead.
+	pub restitution: Option<f32>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GrabbableSettings {
 	/// Max distance that you can be to start grabbing
@@ -57,6 +229,16 @@ pub struct GrabbableSettings {
 	pub pointer_mode: PointerMode,
 	/// Should the object be movable by zones?
 	pub zoneable: bool,
+	/// Keep checking whether a zone moved the content while it rests, to report [`GrabbableEvent::EnteredZone`].
+	///
+	/// Off by default since it polls the server the whole time the content is at rest.
+	pub watch_zones: bool,
+	/// None means no undo/redo.
+	pub history: Option<MoveHistorySettings>,
+	/// None means the content can't be scaled.
+	pub scale: Option<ScaleSettings>,
+	/// None means the content is locked to the grab point.
+	pub smoothing: Option<SmoothingSettings>,
 }
 impl Default for GrabbableSettings {
 	fn default() -> Self {
@@ -73,6 +255,10 @@ impl Default for GrabbableSettings {
 			magnet: true,
 			pointer_mode: PointerMode::Parent,
 			zoneable: true,
+			watch_zones: false,
+			history: None,
+			scale: None,
+			smoothing: None,
 		}
 	}
 }
@@ -81,22 +267,56 @@ pub struct Grabbable {
 	root: Spatial,
 	content_parent: Spatial,
 	field: Field,
-	input: InputQueue,
-	grab_action: SingleAction,
+	inputs: Vec<InputQueue>,
+	grab_actions: Vec<SingleAction>,
+	active_handle: usize,
 
 	content_lines: Lines,
 	root_lines: Lines,
+	grab_lines: Lines,
+	/// The grab point, magnet offset and velocity line last drawn in `grab_lines`
+	grab_lines_drawn: Option<GrabLines>,
+	debug_settings: Option<DebugSettings>,
+	debug_refresh_timer: f32,
 	settings: GrabbableSettings,
 
 	pointer_distance: f32,
+	grab_point: Option<Vec3>,
+	magnet_offset: Option<Vec3>,
+	scale: f32,
+	grab_scale: f32,
+	/// The second hand scaling the content and how far it was from the grabbing hand when it joined
+	two_hand_scale: Option<(u64, f32)>,
 	prev_pose: (Vec3, Quat),
 	pose: (Vec3, Quat),
 
+	smoothed_pose: Option<(Vec3, Quat)>,
+	smoothing_velocity: Vec3,
+	lazy_following: bool,
+
 	closest_point_tx: mpsc::Sender<Vec3>,
 	closest_point_rx: mpsc::Receiver<Vec3>,
 
 	linear_velocity: Option<Vec3>,
 	angular_velocity: Option<(Vec3, f32)>,
+
+	bounds: Option<MomentumBounds>,
+	bounds_query_pending: bool,
+	/// How far the content has to move to get back inside the bounds
+	bounds_tx: mpsc::Sender<Option<Vec3>>,
+	bounds_rx: mpsc::Receiver<Option<Vec3>>,
+
+	zone_watch: Option<ZoneWatch>,
+	/// Bumped whenever the content gets moved here, so poses from before then are ignored
+	zone_watch_generation: u32,
+	zone_pose_tx: mpsc::Sender<(u32, GrabbablePose)>,
+	zone_pose_rx: mpsc::Receiver<(u32, GrabbablePose)>,
+
+	history: MoveHistory,
+	resting_pose_tx: mpsc::Sender<(GrabbablePose, bool)>,
+	resting_pose_rx: mpsc::Receiver<(GrabbablePose, bool)>,
+
+	events: Vec<GrabbableEvent>,
 }
 impl Grabbable {
 	pub fn create(
@@ -105,96 +325,195 @@ impl Grabbable {
 		field: &Field,
 		settings: GrabbableSettings,
 	) -> Result<Self, NodeError> {
-		let input =
-			InputHandler::create(content_space.client()?.get_root(), Transform::none(), field)?
-				.queue()?;
-		let root = Spatial::create(input.handler(), Transform::none(), false)?;
+		Self::create_with_handles(content_space, content_transform, field, &[], settings)
+	}
+	/// Only the handle fields (e.g. a title bar) start grabbing, so the content underneath stays interactive.
+	///
+	/// `field` is still used for the magnet. If `handles` is empty this is the same as `create`.
+	pub fn create_with_handles(
+		content_space: &impl SpatialRefAspect,
+		content_transform: Transform,
+		field: &Field,
+		handles: &[&Field],
+		settings: GrabbableSettings,
+	) -> Result<Self, NodeError> {
+		let client = content_space.client()?;
+		let content_field = [field];
+		let grab_fields = if handles.is_empty() {
+			&content_field[..]
+		} else {
+			handles
+		};
+		let inputs = grab_fields
+			.iter()
+			.map(|field| {
+				InputHandler::create(client.get_root(), Transform::none(), *field)?.queue()
+			})
+			.collect::<Result<Vec<_>, NodeError>>()?;
+		let grab_actions = inputs.iter().map(|_| SingleAction::default()).collect();
+		let root = Spatial::create(inputs[0].handler(), Transform::none(), false)?;
 		let content_parent =
-			Spatial::create(input.handler(), Transform::none(), settings.zoneable)?;
+			Spatial::create(inputs[0].handler(), Transform::none(), settings.zoneable)?;
 		content_parent.set_relative_transform(content_space, content_transform)?;
 
 		let content_lines = Lines::create(&content_parent, Transform::identity(), &[])?;
 		let root_lines = Lines::create(&root, Transform::identity(), &[])?;
+		let grab_lines = Lines::create(inputs[0].handler(), Transform::identity(), &[])?;
 
 		let (closest_point_tx, closest_point_rx) = mpsc::channel(1);
-		Ok(Grabbable {
+		let (bounds_tx, bounds_rx) = mpsc::channel(1);
+		let (zone_pose_tx, zone_pose_rx) = mpsc::channel(1);
+		let (resting_pose_tx, resting_pose_rx) = mpsc::channel(1);
+		let grabbable = Grabbable {
 			root,
 			content_parent,
-			input,
-			grab_action: SingleAction::default(),
+			inputs,
+			grab_actions,
+			active_handle: 0,
 			field: field.alias(),
 
 			content_lines,
 			root_lines,
+			grab_lines,
+			grab_lines_drawn: None,
+			debug_settings: None,
+			debug_refresh_timer: 0.0,
 			settings,
 
 			pointer_distance: 0.0,
+			grab_point: None,
+			magnet_offset: None,
+			scale: 1.0,
+			grab_scale: 1.0,
+			two_hand_scale: None,
 			prev_pose: (vec3(0.0, 0.0, 0.0), Quat::IDENTITY),
 			pose: (vec3(0.0, 0.0, 0.0), Quat::IDENTITY),
 
+			smoothed_pose: None,
+			smoothing_velocity: Vec3::ZERO,
+			lazy_following: false,
+
 			closest_point_tx,
 			closest_point_rx,
 
 			linear_velocity: None,
 			angular_velocity: None,
-		})
+
+			bounds: None,
+			bounds_query_pending: false,
+			bounds_tx,
+			bounds_rx,
+
+			zone_watch: None,
+			zone_watch_generation: 0,
+			zone_pose_tx,
+			zone_pose_rx,
+
+			history: MoveHistory::default(),
+			resting_pose_tx,
+			resting_pose_rx,
+
+			events: Vec::new(),
+		};
+		// the history needs to know where the content starts out
+		if grabbable.settings.history.is_some() {
+			grabbable.query_resting_pose(false);
+		}
+		Ok(grabbable)
 	}
 	pub fn update(&mut self, info: &FrameInfo) -> Result<(), NodeError> {
-		self.grab_action.update(
-			true,
-			&self.input,
-			|input| {
-				let max_distance = self.settings.max_distance;
-				match &input.input {
+		self.events.clear();
+		let max_distance = self.settings.max_distance;
+		// a second hand scales the content instead of taking it
+		let change_actor = !self.settings.scale.is_some_and(|s| s.two_handed);
+		for (input, grab_action) in self.inputs.iter().zip(&mut self.grab_actions) {
+			grab_action.update(
+				change_actor,
+				input,
+				|input| match &input.input {
 					InputDataType::Hand(h) => {
 						h.thumb.tip.distance < max_distance && h.index.tip.distance < max_distance
 					}
 					_ => input.distance < max_distance,
-				}
-			},
-			|data| {
-				data.datamap.with_data(|datamap| match &data.input {
-					InputDataType::Hand(_) => datamap.idx("pinch_strength").as_f32() > 0.90,
-					_ => datamap.idx("grab").as_f32() > 0.90,
-				})
-			},
-		);
+				},
+				Self::grabbing,
+			);
+		}
+		// only switch handles when the current one isn't grabbing so grabs can't be stolen
+		let previous_handle = self.active_handle;
+		if !self.grab_action().actor_acting() {
+			if let Some(acting) = self.grab_actions.iter().position(|a| a.actor_acting()) {
+				self.active_handle = acting;
+			}
+		}
+		// the content never stopped being held, it just moved from one handle to another
+		let handed_over = self.active_handle != previous_handle
+			&& self.grab_actions[previous_handle].actor_stopped();
+		let started = self.grab_action().actor_started() || handed_over;
+		let stopped = self.grab_action().actor_stopped();
+
+		while let Ok((pose, settled)) = self.resting_pose_rx.try_recv() {
+			let max_entries = self.settings.history.unwrap_or_default().max_entries;
+			self.history.rested(pose, max_entries);
+			if settled {
+				self.events.push(GrabbableEvent::SettledAt { pose });
+			}
+		}
+
+		if started {
+			if handed_over {
+				// the magnet was for the old grab point
+				let _ = self.closest_point_rx.try_recv();
+				self.magnet_offset.take();
+			} else if let Some(history_settings) = self.settings.history {
+				self.history.record(history_settings.max_entries);
+			}
 
-		if self.grab_action.actor_started() {
 			// Make sure we can directly apply the grab data to the content parent
-			let actor = self.grab_action.actor().unwrap();
+			let actor = self.grab_action().actor().cloned().unwrap();
 			if let InputDataType::Pointer(pointer) = &actor.input {
 				// store the pointer distance so we can keep it at the correct point
 				self.pointer_distance =
 					Vec3::from(pointer.origin).distance(pointer.deepest_point.into());
 			}
+			self.two_hand_scale.take();
+			self.smoothed_pose.take();
 
 			// gotta reparent to the handler to set the root offset without moving it
 			self.content_parent
-				.set_spatial_parent_in_place(self.input.handler())
-				.unwrap();
+				.set_spatial_parent_in_place(self.handler())?;
 		}
 
-		if let Some(actor) = self.grab_action.actor().cloned() {
+		if let Some(actor) = self.grab_action().actor().cloned() {
 			let (mut position, rotation) = self.input_position_rotation(&actor);
+			self.update_scale(&actor);
 			debug!(?position, ?rotation, id = actor.id, "Currently grabbing");
+			self.grab_point.replace(position);
 
 			if self.settings.magnet {
 				if let Ok(closest_point) = self.closest_point_rx.try_recv() {
-					position -= rotation * closest_point;
+					let magnet_offset = Self::magnet_offset(rotation, closest_point, self.scale);
+					position -= magnet_offset;
+					self.magnet_offset.replace(magnet_offset);
 					let _ = self.closest_point_tx.try_send(closest_point);
 				}
 			}
+			let (position, rotation) = self.smooth_pose(position, rotation, info.delta);
 			let transform_spatial = match (self.settings.pointer_mode, &actor.input) {
-				(PointerMode::Align, InputDataType::Pointer(_)) => self.content_parent(),
+				(PointerMode::Align | PointerMode::Billboard, InputDataType::Pointer(_)) => {
+					self.content_parent()
+				}
 				_ => &self.root,
 			};
-			transform_spatial
-				.set_relative_transform(
-					self.input.handler(),
-					Transform::from_translation_rotation(position, rotation),
-				)
-				.unwrap();
+			let transform = match self.settings.scale {
+				Some(_) => Transform::from_translation_rotation_scale(
+					position,
+					rotation,
+					Vec3::splat(self.scale),
+				),
+				None => Transform::from_translation_rotation(position, rotation),
+			};
+			transform_spatial.set_relative_transform(self.handler(), transform)?;
 
 			self.prev_pose = self.pose;
 			self.pose = (position, rotation);
@@ -213,102 +532,447 @@ impl Grabbable {
 			}
 		}
 
-		if self.grab_action.actor_started() {
-			debug!(
-				id = self.grab_action.actor().as_ref().unwrap().id,
-				"Started grabbing"
-			);
-			self.content_parent.set_zoneable(false).unwrap();
+		if started {
+			let id = self.grab_action().actor().as_ref().unwrap().id;
+			if handed_over {
+				debug!(id, "Handed off to another handle");
+				self.events.push(GrabbableEvent::HandedOff);
+			} else {
+				debug!(id, "Started grabbing");
+				self.events.push(GrabbableEvent::PickedUp);
+				self.content_parent.set_zoneable(false)?;
+				self.stop_watching_for_zone();
+			}
 			self.content_parent
-				.set_spatial_parent_in_place(&self.root)
-				.unwrap();
-
-			'magnet: {
-				if self.settings.magnet {
-					// if we have magnet strength, store the closest point so we can lerp that to the grab point
-					let grab_data = self.grab_action.actor().unwrap().clone();
-					// pointers are just too unstable to magnet
-					if let InputDataType::Pointer(_) = &grab_data.input {
-						break 'magnet;
-					}
-					let field = self.field.alias();
-					let root = self.root.alias();
-					let closest_point_tx = self.closest_point_tx.clone();
-					tokio::task::spawn(async move {
-						let result = field.closest_point(&root, [0.0; 3]).await.unwrap();
-						// if let Ok(result) = result {
-						let _ = closest_point_tx.send(result.into()).await;
-						// }
-					});
-				}
+				.set_spatial_parent_in_place(&self.root)?;
+
+			// store the closest point so we can pull it onto the grab point
+			if self.settings.magnet {
+				self.request_magnet_point();
 			}
 		}
 
-		if self.grab_action.actor_stopped() {
+		if self.grab_action().actor_changed() {
+			debug!(
+				id = self.grab_action().actor().as_ref().unwrap().id,
+				"Handed off"
+			);
+			self.events.push(GrabbableEvent::HandedOff);
+		}
+
+		if stopped {
 			debug!("Stopped grabbing");
+			self.events.push(GrabbableEvent::Released {
+				velocity: self.linear_velocity(),
+			});
 
 			// drain the closest point queue
 			let _ = self.closest_point_rx.try_recv();
+			self.grab_point.take();
+			self.magnet_offset.take();
+
+			// momentum makes it zoneable once it runs out instead
+			if self.linear_velocity.is_none() {
+				self.become_zoneable()?;
+			}
 		}
 
-		if !self.grab_action.actor_acting() {
+		if !self.grab_action().actor_acting() {
+			if let Some(settings) = self.settings.history {
+				let progress = info.delta / settings.animation_duration;
+				if let Some(pose) = self.history.update_animation(progress) {
+					self.content_parent
+						.set_relative_transform(self.handler(), pose.into())?;
+					if self.settings.scale.is_some() {
+						self.scale = pose.scale;
+					}
+					if self.history.animation.is_none() {
+						self.events.push(GrabbableEvent::SettledAt { pose });
+						self.restart_zone_watch();
+					}
+				}
+			}
+
+			let was_moving = self.linear_velocity.is_some() || self.angular_velocity.is_some();
+			self.apply_bounds();
 			if let Some(settings) = self.settings.linear_momentum {
-				self.apply_linear_momentum(info, settings);
+				self.apply_linear_momentum(info, settings)?;
 			}
 			if let Some(settings) = self.settings.angular_momentum {
 				self.apply_angular_momentum(info, settings);
 			}
 
-			if self.linear_velocity.is_some() || self.angular_velocity.is_some() {
+			let moving = self.linear_velocity.is_some() || self.angular_velocity.is_some();
+			if moving {
 				self.root.set_relative_transform(
-					self.input.handler(),
+					self.handler(),
 					Transform::from_translation_rotation(self.pose.0, self.pose.1),
 				)?;
 			}
+			if !moving && (was_moving || stopped) {
+				self.query_resting_pose(true);
+			}
+			if !moving && self.history.animation.is_none() {
+				self.watch_for_zone(info.delta);
+			}
 		}
 
+		self.update_debug(info);
 		Ok(())
 	}
+	fn update_debug(&mut self, info: &FrameInfo) {
+		let Some(settings) = self.debug_settings else {
+			return;
+		};
+		// the content can change size at any time so the bounds have to be polled
+		self.debug_refresh_timer -= info.delta;
+		if self.debug_refresh_timer <= 0.0 {
+			self.debug_refresh_timer = settings.refresh_interval;
+			self.refresh_debug_bounds(settings);
+		}
+
+		let drawn = (
+			self.grab_point,
+			self.grab_point.and(self.magnet_offset),
+			self.linear_velocity.map(|velocity| (self.pose.0, velocity)),
+		);
+		if self.grab_lines_drawn == Some(drawn) {
+			return;
+		}
+		self.grab_lines_drawn.replace(drawn);
+
+		let mut lines = Vec::new();
+		if let Some(grab_point) = self.grab_point {
+			lines.push(
+				circle(16, 0.0, 0.005)
+					.thickness(settings.line_thickness)
+					.color(settings.line_color)
+					.transform(Mat4::from_translation(grab_point)),
+			);
+			if let Some(magnet_offset) = self.magnet_offset {
+				lines.push(
+					line_from_points(vec![grab_point, grab_point - magnet_offset])
+						.thickness(settings.line_thickness)
+						.color(settings.line_color),
+				);
+			}
+		}
+		if let Some(velocity) = self.linear_velocity {
+			// show where it'd be in a tenth of a second
+			lines.push(
+				line_from_points(vec![self.pose.0, self.pose.0 + velocity * 0.1])
+					.thickness(settings.line_thickness)
+					.color(rgba_linear!(1.0, 0.75, 0.0, 1.0)),
+			);
+		}
+		let _ = self.grab_lines.set_lines(&lines);
+	}
+	fn refresh_debug_bounds(&self, settings: DebugSettings) {
+		let content_lines = self.content_lines.alias();
+		let content_parent = self.content_parent.alias();
+		tokio::task::spawn(async move {
+			if let Ok(bounds) = content_parent.get_local_bounding_box().await {
+				let _ = content_lines.set_lines(
+					&bounding_box(bounds)
+						.into_iter()
+						.map(|l| {
+							l.color(settings.line_color)
+								.thickness(settings.line_thickness)
+						})
+						.collect::<Vec<_>>(),
+				);
+			}
+		});
+	}
+	/// How far the root has to sit from the grab point to put the closest point on it, in handler space.
+	///
+	/// The closest point is relative to the root, which gets scaled along with the content.
+	fn magnet_offset(rotation: Quat, closest_point: Vec3, scale: f32) -> Vec3 {
+		rotation * (closest_point * scale)
+	}
+	/// Find the closest point on the field to the grab point, so the content can be pulled onto it
+	fn request_magnet_point(&self) {
+		// pointers are just too unstable to magnet
+		if let Some(InputDataType::Pointer(_)) = self.grab_action().actor().map(|a| &a.input) {
+			return;
+		}
+		let field = self.field.alias();
+		let root = self.root.alias();
+		let closest_point_tx = self.closest_point_tx.clone();
+		tokio::task::spawn(async move {
+			// the field may have been destroyed, in which case just don't magnet
+			match field.closest_point(&root, [0.0; 3]).await {
+				Ok(result) => {
+					let _ = closest_point_tx.send(result.into()).await;
+				}
+				Err(error) => debug!(?error, "Couldn't get closest point for magnet"),
+			}
+		});
+	}
+	fn query_resting_pose(&self, settled: bool) {
+		let content_parent = self.content_parent.alias();
+		let handler = self.handler().alias();
+		let resting_pose_tx = self.resting_pose_tx.clone();
+		tokio::task::spawn(async move {
+			if let Ok(transform) = content_parent.get_transform(&handler).await {
+				let _ = resting_pose_tx.send((transform.into(), settled)).await;
+			}
+		});
+	}
 	fn input_position_rotation(&mut self, input: &InputData) -> (Vec3, Quat) {
 		match &input.input {
-			InputDataType::Hand(h) => (
-				Vec3::from(h.thumb.tip.position).lerp(Vec3::from(h.index.tip.position), 0.5),
-				h.palm.rotation.into(),
-			),
+			InputDataType::Hand(h) => (Self::pinch_point(h), h.palm.rotation.into()),
 			InputDataType::Pointer(p) => {
 				let scroll = input
 					.datamap
 					.with_data(|d| d.idx("scroll_continuous").as_vector().idx(1).as_f32());
-				self.pointer_distance += scroll * 0.01;
+				match &self.settings.scale {
+					Some(scale_settings) => {
+						self.scale = (self.scale * (scroll * scale_settings.scroll_speed).exp())
+							.clamp(scale_settings.min, scale_settings.max);
+					}
+					None => self.pointer_distance += scroll * 0.01,
+				}
 				let grab_point =
 					Vec3::from(p.origin) + (Vec3::from(p.direction()) * self.pointer_distance);
 				match self.settings.pointer_mode {
 					PointerMode::Parent => (p.origin.into(), p.orientation.into()),
 					PointerMode::Align => (grab_point, swing_direction(p.direction().into())),
 					PointerMode::Move => (grab_point, Quat::IDENTITY),
+					PointerMode::Billboard => {
+						// flatten the ray so only yaw is left
+						let direction = Vec3::from(p.direction()) * vec3(1.0, 0.0, 1.0);
+						(grab_point, swing_direction(direction.normalize_or_zero()))
+					}
+					PointerMode::HitOffset => (grab_point, swing_direction(p.direction().into())),
 				}
 			}
 			InputDataType::Tip(t) => (t.origin.into(), t.orientation.into()),
 		}
 	}
+	fn smooth_pose(&mut self, position: Vec3, rotation: Quat, delta: f32) -> (Vec3, Quat) {
+		let Some(settings) = self.settings.smoothing else {
+			return (position, rotation);
+		};
+		// start right at the grab point so grabbing doesn't make the content jump
+		let Some((current_position, current_rotation)) = self.smoothed_pose else {
+			self.smoothed_pose.replace((position, rotation));
+			self.smoothing_velocity = Vec3::ZERO;
+			self.lazy_following = false;
+			return (position, rotation);
+		};
+
+		let (target_position, target_rotation) = match settings.dead_zone {
+			Some(dead_zone) => {
+				let distance = current_position.distance(position);
+				if distance > dead_zone {
+					self.lazy_following = true;
+				} else if distance < dead_zone * 0.1 {
+					self.lazy_following = false;
+				}
+				if self.lazy_following {
+					(position, rotation)
+				} else {
+					(current_position, current_rotation)
+				}
+			}
+			None => (position, rotation),
+		};
+
+		let smoothed_position = smooth_damp(
+			current_position,
+			target_position,
+			&mut self.smoothing_velocity,
+			settings.position_time,
+			delta,
+		);
+		let smoothed_rotation = if settings.rotation_time > 0.0 {
+			current_rotation.slerp(
+				target_rotation,
+				1.0 - (-delta / settings.rotation_time).exp(),
+			)
+		} else {
+			target_rotation
+		};
+		self.smoothed_pose
+			.replace((smoothed_position, smoothed_rotation));
+		(smoothed_position, smoothed_rotation)
+	}
+	fn grabbing(input: &InputData) -> bool {
+		input.datamap.with_data(|datamap| match &input.input {
+			InputDataType::Hand(_) => datamap.idx("pinch_strength").as_f32() > 0.90,
+			_ => datamap.idx("grab").as_f32() > 0.90,
+		})
+	}
+	fn pinch_point(hand: &Hand) -> Vec3 {
+		Vec3::from(hand.thumb.tip.position).lerp(Vec3::from(hand.index.tip.position), 0.5)
+	}
+	/// Closest the hands count as being when the second one joins, so scaling never divides by almost nothing
+	const MIN_TWO_HAND_DISTANCE: f32 = 0.05;
+	fn update_scale(&mut self, actor: &InputData) {
+		let (Some(settings), InputDataType::Hand(hand)) = (self.settings.scale, &actor.input)
+		else {
+			return;
+		};
+		if !settings.two_handed {
+			return;
+		}
+		// the second hand gets captured like the first, it just doesn't take over the grab
+		let second_hand = self.inputs[self.active_handle]
+			.input()
+			.into_keys()
+			.find_map(|input| match &input.input {
+				InputDataType::Hand(other)
+					if input.id != actor.id && input.captured && Self::grabbing(&input) =>
+				{
+					Some((input.id, Self::pinch_point(other)))
+				}
+				_ => None,
+			});
+		let Some((id, second_point)) = second_hand else {
+			self.two_hand_scale.take();
+			return;
+		};
+		let distance = Self::pinch_point(hand).distance(second_point);
+		match self.two_hand_scale {
+			Some((scale_id, start_distance)) if scale_id == id => {
+				self.scale =
+					(self.grab_scale * distance / start_distance).clamp(settings.min, settings.max);
+			}
+			_ => {
+				self.two_hand_scale
+					.replace((id, distance.max(Self::MIN_TWO_HAND_DISTANCE)));
+				self.grab_scale = self.scale;
+			}
+		}
+	}
+	fn apply_bounds(&mut self) {
+		let Some(bounds) = &self.bounds else {
+			return;
+		};
+		if let Ok(result) = self.bounds_rx.try_recv() {
+			self.bounds_query_pending = false;
+			if let (Some(correction), Some(velocity)) = (result, &mut self.linear_velocity) {
+				// the content kept moving while the server answered, so push it back in by how far out it was
+				// instead of putting it back where it was
+				self.pose.0 += correction;
+				let normal = -correction.normalize_or_zero();
+				match bounds.restitution {
+					Some(restitution) if velocity.dot(normal) > 0.0 => {
+						*velocity = (*velocity - 2.0 * velocity.dot(normal) * normal) * restitution;
+					}
+					Some(_) => (),
+					// zero instead of none so the momentum stops like usual and the content becomes zoneable
+					None => *velocity = Vec3::ZERO,
+				}
+				debug!(?correction, "Momentum hit bounds");
+			}
+		}
+
+		if self.linear_velocity.is_none() || self.bounds_query_pending {
+			return;
+		}
+		self.bounds_query_pending = true;
+		let field = bounds.field.alias();
+		let handler = self.handler().alias();
+		let content_parent = self.content_parent.alias();
+		let bounds_tx = self.bounds_tx.clone();
+		tokio::task::spawn(async move {
+			let result = async {
+				let content = content_parent.get_transform(&handler).await?;
+				let point = content.translation.map(Vec3::from).unwrap_or_default();
+				// positive distance means the content is outside the field
+				if field.distance(&handler, point).await? <= 0.0 {
+					return Ok(None);
+				}
+				let closest_point = Vec3::from(field.closest_point(&handler, point).await?);
+				Ok::<_, NodeError>(Some(closest_point - point))
+			}
+			.await;
+			let _ = bounds_tx.send(result.ok().flatten()).await;
+		});
+	}
 	const LINEAR_VELOCITY_STOP_THRESHOLD: f32 = 0.001;
-	fn apply_linear_momentum(&mut self, info: &FrameInfo, settings: MomentumSettings) {
+	fn apply_linear_momentum(
+		&mut self,
+		info: &FrameInfo,
+		settings: MomentumSettings,
+	) -> Result<(), NodeError> {
 		let Some(velocity) = &mut self.linear_velocity else {
-			return;
+			return Ok(());
 		};
 		let delta = info.delta;
 		if velocity.length_squared() < Self::LINEAR_VELOCITY_STOP_THRESHOLD {
 			self.linear_velocity.take();
 
 			// lets us slide the grabbable into a zone seamlessly
-			self.content_parent
-				.set_zoneable(self.settings.zoneable)
-				.unwrap();
+			self.become_zoneable()?;
 		} else {
 			*velocity *= (1.0 - settings.drag * delta).clamp(0.0, 1.0);
 			self.pose.0 += *velocity * delta;
 			trace!(?velocity, "linear momentum");
 		}
+		Ok(())
+	}
+	fn become_zoneable(&mut self) -> Result<(), NodeError> {
+		self.content_parent.set_zoneable(self.settings.zoneable)?;
+		if self.settings.zoneable {
+			self.events.push(GrabbableEvent::BecameZoneable);
+			if self.settings.watch_zones {
+				self.zone_watch.replace(ZoneWatch::default());
+				self.zone_watch_generation += 1;
+			}
+		}
+		Ok(())
+	}
+	fn stop_watching_for_zone(&mut self) {
+		self.zone_watch.take();
+		self.zone_watch_generation += 1;
+	}
+	/// The content was moved here on purpose, so start comparing against where it is now
+	fn restart_zone_watch(&mut self) {
+		if self.zone_watch.is_some() {
+			self.zone_watch.replace(ZoneWatch::default());
+			self.zone_watch_generation += 1;
+		}
+	}
+	/// How often to check if a zone has moved the content, in seconds
+	const ZONE_CHECK_INTERVAL: f32 = 0.25;
+	fn watch_for_zone(&mut self, delta: f32) {
+		let Some(watch) = &mut self.zone_watch else {
+			return;
+		};
+		let mut entered_zone = false;
+		while let Ok((generation, pose)) = self.zone_pose_rx.try_recv() {
+			if generation != self.zone_watch_generation {
+				continue;
+			}
+			match watch.resting_pose {
+				None => watch.resting_pose = Some(pose),
+				Some(resting_pose) => entered_zone |= ZoneWatch::moved(resting_pose, pose),
+			}
+		}
+		if entered_zone {
+			debug!("Entered zone");
+			self.events.push(GrabbableEvent::EnteredZone);
+			self.stop_watching_for_zone();
+			return;
+		}
+
+		watch.timer -= delta;
+		if watch.timer > 0.0 {
+			return;
+		}
+		watch.timer = Self::ZONE_CHECK_INTERVAL;
+		let content_parent = self.content_parent.alias();
+		let handler = self.handler().alias();
+		let generation = self.zone_watch_generation;
+		let zone_pose_tx = self.zone_pose_tx.clone();
+		tokio::task::spawn(async move {
+			if let Ok(transform) = content_parent.get_transform(&handler).await {
+				let _ = zone_pose_tx.send((generation, transform.into())).await;
+			}
+		});
 	}
 	const ANGULAR_VELOCITY_STOP_THRESHOLD: f32 = 0.001;
 	fn apply_angular_momentum(&mut self, info: &FrameInfo, settings: MomentumSettings) {
@@ -335,7 +999,7 @@ impl Grabbable {
 		self.linear_velocity.take();
 	}
 	pub fn just_stopped_moving(&self) -> bool {
-		!self.grab_action.actor_acting()
+		!self.grab_action().actor_acting()
 			&& self.linear_velocity.is_some()
 			&& self.linear_velocity.unwrap().length_squared() < Self::LINEAR_VELOCITY_STOP_THRESHOLD
 	}
@@ -346,46 +1010,468 @@ impl Grabbable {
 		self.angular_velocity.take();
 	}
 	pub fn just_stopped_rotating(&self) -> bool {
-		!self.grab_action.actor_acting()
+		!self.grab_action().actor_acting()
 			&& self.angular_velocity.is_some()
 			&& self.angular_velocity.unwrap().1 < Self::ANGULAR_VELOCITY_STOP_THRESHOLD
 	}
 
+	/// Get the current pose of the content relative to the client root.
+	pub async fn pose(&self) -> Result<GrabbablePose, NodeError> {
+		let transform = self.content_parent.get_transform(self.handler()).await?;
+		Ok(transform.into())
+	}
+	/// Move the content to a pose relative to the client root, e.g. one restored from `ClientState`.
+	///
+	/// Any current momentum is stopped so the content stays where it's put.
+	/// Returns false without moving anything while the grabbable is being held, since the content follows the grab point.
+	pub fn set_pose(&mut self, pose: GrabbablePose) -> Result<bool, NodeError> {
+		if self.grab_action().actor_acting() {
+			return Ok(false);
+		}
+		self.history.animation.take();
+		self.history.resting_pose.replace(pose);
+		self.stop_momentum()?;
+		if self.settings.scale.is_some() {
+			self.scale = pose.scale;
+		}
+		self.content_parent.set_spatial_parent(self.handler())?;
+		self.content_parent
+			.set_relative_transform(self.handler(), pose.into())?;
+		self.restart_zone_watch();
+		Ok(true)
+	}
+	/// Cut momentum short, cleaning up the same way as when it runs out on its own.
+	fn stop_momentum(&mut self) -> Result<(), NodeError> {
+		let was_sliding = self.linear_velocity.take().is_some();
+		let was_rotating = self.angular_velocity.take().is_some();
+		if was_sliding {
+			self.become_zoneable()?;
+		}
+		if (was_sliding || was_rotating) && self.history.resting_pose.is_none() {
+			self.query_resting_pose(true);
+		}
+		Ok(())
+	}
+
+	/// Animate back to where the content was before the last move.
+	///
+	/// Returns false if there's nothing to undo, history is disabled or the grabbable is being held.
+	pub fn undo(&mut self) -> Result<bool, NodeError> {
+		if self.settings.history.is_none() || self.grab_action().actor_acting() || !self.can_undo()
+		{
+			return Ok(false);
+		}
+		self.stop_momentum()?;
+		Ok(self.history.undo())
+	}
+	/// Animate forward to where the content was before the last undo.
+	///
+	/// Returns false if there's nothing to redo, history is disabled or the grabbable is being held.
+	pub fn redo(&mut self) -> Result<bool, NodeError> {
+		if self.settings.history.is_none() || self.grab_action().actor_acting() || !self.can_redo()
+		{
+			return Ok(false);
+		}
+		self.stop_momentum()?;
+		Ok(self.history.redo())
+	}
+	pub fn can_undo(&self) -> bool {
+		self.history.resting_pose.is_some() && !self.history.undo.is_empty()
+	}
+	pub fn can_redo(&self) -> bool {
+		self.history.resting_pose.is_some() && !self.history.redo.is_empty()
+	}
+
+	/// Keep thrown content inside a field, None lets momentum carry it anywhere.
+	pub fn set_bounds(&mut self, bounds: Option<MomentumBounds>) {
+		self.bounds = bounds;
+	}
+
+	/// Everything that happened during the last update.
+	pub fn events(&self) -> &[GrabbableEvent] {
+		&self.events
+	}
+
+	/// Uniform scale applied to the content by grabbing, always 1.0 if scaling is disabled.
+	pub fn scale(&self) -> f32 {
+		self.scale
+	}
+
+	/// The grab action of the handle that's grabbing, or last grabbed.
 	pub fn grab_action(&self) -> &SingleAction {
-		&self.grab_action
+		&self.grab_actions[self.active_handle]
+	}
+	fn handler(&self) -> &InputHandler {
+		self.inputs[0].handler()
 	}
 	pub fn content_parent(&self) -> &Spatial {
 		&self.content_parent
 	}
 
 	pub fn set_enabled(&self, enabled: bool) -> Result<(), NodeError> {
-		self.input.handler().set_enabled(enabled)
+		for input in &self.inputs {
+			input.handler().set_enabled(enabled)?;
+		}
+		Ok(())
 	}
 }
+/// Grab point, magnet offset and where the content is along with its velocity
+type GrabLines = (Option<Vec3>, Option<Vec3>, Option<(Vec3, Vec3)>);
+
+#[derive(Debug, Default)]
+struct MoveHistory {
+	undo: Vec<GrabbablePose>,
+	redo: Vec<GrabbablePose>,
+	/// Where the content was when it last came to rest, relative to the client root
+	resting_pose: Option<GrabbablePose>,
+	/// Grabbed before the resting pose arrived, so it goes straight into `undo` once it does
+	pending_record: bool,
+	animation: Option<PoseAnimation>,
+}
+impl MoveHistory {
+	/// Save the resting pose as an undo point since the content is about to move
+	fn record(&mut self, max_entries: usize) {
+		// grabbing mid-animation means the content never came to rest
+		let interrupted = self.animation.take().is_some();
+		if interrupted {
+			self.resting_pose.take();
+		}
+		// anything undone can't be redone once the content goes somewhere new
+		self.redo.clear();
+		let Some(pose) = self.resting_pose.take() else {
+			// the query for where it came to rest was sent before the grab, so the reply is still where it started
+			self.pending_record = !interrupted;
+			return;
+		};
+		self.push_undo(pose, max_entries);
+	}
+	/// The content came to rest at `pose`
+	fn rested(&mut self, pose: GrabbablePose, max_entries: usize) {
+		if self.pending_record {
+			self.pending_record = false;
+			self.push_undo(pose, max_entries);
+		} else {
+			self.resting_pose.replace(pose);
+		}
+	}
+	fn push_undo(&mut self, pose: GrabbablePose, max_entries: usize) {
+		self.undo.push(pose);
+		if self.undo.len() > max_entries {
+			self.undo.drain(..self.undo.len() - max_entries);
+		}
+	}
+	fn undo(&mut self) -> bool {
+		let Some(current) = self.resting_pose else {
+			return false;
+		};
+		let Some(previous) = self.undo.pop() else {
+			return false;
+		};
+		self.redo.push(current);
+		self.animate_to(previous);
+		true
+	}
+	fn redo(&mut self) -> bool {
+		let Some(current) = self.resting_pose else {
+			return false;
+		};
+		let Some(next) = self.redo.pop() else {
+			return false;
+		};
+		self.undo.push(current);
+		self.animate_to(next);
+		true
+	}
+	fn animate_to(&mut self, to: GrabbablePose) {
+		let from = match &self.animation {
+			Some(animation) => animation.current(),
+			None => self.resting_pose.unwrap_or(to),
+		};
+		self.animation.replace(PoseAnimation::new(from, to));
+		self.resting_pose.replace(to);
+	}
+	/// Advance the animation, returning the pose the content should be at
+	fn update_animation(&mut self, progress: f32) -> Option<GrabbablePose> {
+		let animation = self.animation.as_mut()?;
+		let pose = animation.advance(progress);
+		if animation.finished() {
+			self.animation.take();
+		}
+		Some(pose)
+	}
+}
+
+/// Zones capturing the content is only noticeable by them moving it
+#[derive(Debug, Default)]
+struct ZoneWatch {
+	timer: f32,
+	/// Where the content was when watching started
+	resting_pose: Option<GrabbablePose>,
+}
+impl ZoneWatch {
+	fn moved(from: GrabbablePose, to: GrabbablePose) -> bool {
+		from.position().distance(to.position()) > 0.001
+			|| from.rotation().angle_between(to.rotation()) > 0.01
+			|| (from.scale - to.scale).abs() > 0.001
+	}
+}
+
+/// Eases content from one pose to another, used for undo/redo and snapping into docks.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PoseAnimation {
+	pub from: GrabbablePose,
+	pub to: GrabbablePose,
+	/// From 0 to 1
+	pub progress: f32,
+}
+impl PoseAnimation {
+	pub fn new(from: GrabbablePose, to: GrabbablePose) -> Self {
+		PoseAnimation {
+			from,
+			to,
+			progress: 0.0,
+		}
+	}
+	/// Move `progress` further along, returning the pose to be at now
+	pub fn advance(&mut self, progress: f32) -> GrabbablePose {
+		self.progress = (self.progress + progress).min(1.0);
+		self.current()
+	}
+	pub fn finished(&self) -> bool {
+		self.progress >= 1.0
+	}
+	pub fn current(&self) -> GrabbablePose {
+		// smoothstep so it eases in and out
+		let t = self.progress * self.progress * (3.0 - 2.0 * self.progress);
+		GrabbablePose::new(
+			self.from.position().lerp(self.to.position(), t),
+			self.from.rotation().slerp(self.to.rotation(), t),
+		)
+		.with_scale(self.from.scale.lerp_bounded(self.to.scale, t))
+	}
+}
+
 impl VisualDebug for Grabbable {
-	fn set_debug(&mut self, settings: Option<crate::DebugSettings>) {
+	fn set_debug(&mut self, settings: Option<DebugSettings>) {
+		self.debug_settings = settings;
+		self.grab_lines_drawn.take();
 		if let Some(settings) = settings {
 			let _ = self
 				.root_lines
 				.set_lines(&axes(0.01, settings.line_thickness));
-			let content_lines = self.content_lines.alias();
-			let content_parent = self.content_parent.alias();
-			tokio::task::spawn(async move {
-				if let Ok(bounds) = content_parent.get_local_bounding_box().await {
-					let _ = content_lines.set_lines(
-						&bounding_box(bounds)
-							.into_iter()
-							.map(|l| {
-								l.color(settings.line_color)
-									.thickness(settings.line_thickness)
-							})
-							.collect::<Vec<_>>(),
-					);
-				}
-			});
+			self.debug_refresh_timer = settings.refresh_interval;
+			self.refresh_debug_bounds(settings);
 		} else {
 			let _ = self.content_lines.set_lines(&[]);
 			let _ = self.root_lines.set_lines(&[]);
+			let _ = self.grab_lines.set_lines(&[]);
 		}
 	}
 }
+
+#[cfg(test)]
+fn fake_tip() -> std::sync::Arc<InputData> {
+	use stardust_xr_fusion::{core::values::Datamap, input::Tip};
+	#[derive(Serialize)]
+	struct GrabData {
+		grab: f32,
+	}
+	std::sync::Arc::new(InputData {
+		id: 0,
+		input: InputDataType::Tip(Tip {
+			origin: [0.0; 3].into(),
+			orientation: Quat::IDENTITY.into(),
+		}),
+		distance: 0.0,
+		datamap: Datamap::from_typed(GrabData { grab: 1.0 }).unwrap(),
+		order: 0,
+		captured: true,
+	})
+}
+
+#[tokio::test]
+async fn grabbable_destroyed_field() {
+	use stardust_xr_fusion::fields::Shape;
+	let (client, _event_loop) = stardust_xr_fusion::client::Client::connect_with_async_loop()
+		.await
+		.unwrap();
+
+	let field =
+		Field::create(client.get_root(), Transform::identity(), Shape::Sphere(0.1)).unwrap();
+	let mut grabbable = Grabbable::create(
+		client.get_root(),
+		Transform::none(),
+		&field,
+		GrabbableSettings::default(),
+	)
+	.unwrap();
+	drop(field);
+
+	// no input method can grab it for real, so pretend a tip started grabbing
+	grabbable.grab_actions[0].set_actor(fake_tip());
+	grabbable.request_magnet_point();
+
+	let info = FrameInfo {
+		delta: 1.0 / 90.0,
+		elapsed: 0.0,
+	};
+	for _ in 0..90 {
+		grabbable.update(&info).unwrap();
+		tokio::task::yield_now().await;
+	}
+	assert!(grabbable.grab_action().actor_acting());
+	assert!(grabbable.grab_point.is_some());
+	assert_eq!(grabbable.magnet_offset, None);
+}
+
+#[tokio::test]
+async fn grabbable_destroyed_bounds() {
+	use stardust_xr_fusion::fields::Shape;
+	let (client, _event_loop) = stardust_xr_fusion::client::Client::connect_with_async_loop()
+		.await
+		.unwrap();
+
+	let field =
+		Field::create(client.get_root(), Transform::identity(), Shape::Sphere(0.1)).unwrap();
+	let bounds_field =
+		Field::create(client.get_root(), Transform::identity(), Shape::Sphere(1.0)).unwrap();
+	let mut grabbable = Grabbable::create(
+		client.get_root(),
+		Transform::none(),
+		&field,
+		GrabbableSettings::default(),
+	)
+	.unwrap();
+	grabbable.set_bounds(Some(MomentumBounds {
+		field: bounds_field.alias(),
+		restitution: None,
+	}));
+	drop(bounds_field);
+	grabbable.linear_velocity.replace(vec3(1.0, 0.0, 0.0));
+
+	let info = FrameInfo {
+		delta: 1.0 / 90.0,
+		elapsed: 0.0,
+	};
+	for _ in 0..90 {
+		grabbable.update(&info).unwrap();
+		tokio::task::yield_now().await;
+	}
+	// the bounds are gone so nothing stopped it early, it slid until the drag stopped it
+	assert!(grabbable.linear_velocity().is_none());
+	assert!(grabbable.pose.0.x > 0.05);
+	assert_eq!(grabbable.pose.0.y, 0.0);
+}
+
+#[test]
+fn move_history_undo_redo() {
+	let a = GrabbablePose::new(vec3(0.0, 0.0, 0.0), Quat::IDENTITY);
+	let b = GrabbablePose::new(vec3(1.0, 0.0, 0.0), Quat::IDENTITY);
+	let mut history = MoveHistory::default();
+	history.resting_pose.replace(a);
+	history.record(32);
+	assert_eq!(history.resting_pose, None);
+	history.resting_pose.replace(b);
+
+	assert!(history.undo());
+	let halfway = history.update_animation(0.5).unwrap();
+	assert!(halfway.position().abs_diff_eq(vec3(0.5, 0.0, 0.0), 0.0001));
+	assert_eq!(history.update_animation(0.5), Some(a));
+	assert_eq!(history.update_animation(0.5), None);
+	assert_eq!(history.resting_pose, Some(a));
+	assert!(!history.undo());
+
+	assert!(history.redo());
+	assert_eq!(history.update_animation(1.0), Some(b));
+	assert_eq!(history.resting_pose, Some(b));
+	assert!(!history.redo());
+
+	// moving somewhere new throws away what was undone
+	assert!(history.undo());
+	history.update_animation(1.0);
+	history.record(32);
+	assert!(history.redo.is_empty());
+	assert_eq!(history.undo, vec![a]);
+}
+
+#[test]
+fn move_history_grab_before_resting_pose() {
+	let a = GrabbablePose::new(vec3(0.0, 0.0, 0.0), Quat::IDENTITY);
+	let b = GrabbablePose::new(vec3(1.0, 0.0, 0.0), Quat::IDENTITY);
+	let mut history = MoveHistory::default();
+	// grabbed before the resting pose query came back
+	history.record(32);
+	assert!(history.undo.is_empty());
+	history.rested(a, 32);
+	assert_eq!(history.undo, vec![a]);
+	assert_eq!(history.resting_pose, None);
+
+	// later replies are just where it came to rest
+	history.rested(b, 32);
+	assert_eq!(history.resting_pose, Some(b));
+	assert!(history.undo());
+	assert_eq!(history.update_animation(1.0), Some(a));
+}
+
+#[test]
+fn move_history_max_entries() {
+	let mut history = MoveHistory::default();
+	let poses = (0..5)
+		.map(|i| GrabbablePose::new(vec3(i as f32, 0.0, 0.0), Quat::IDENTITY))
+		.collect::<Vec<_>>();
+	for pose in &poses {
+		history.resting_pose.replace(*pose);
+		history.record(3);
+	}
+	assert_eq!(history.undo, poses[2..]);
+}
+
+#[test]
+fn move_history_grab_during_animation() {
+	let a = GrabbablePose::new(vec3(0.0, 0.0, 0.0), Quat::IDENTITY);
+	let b = GrabbablePose::new(vec3(1.0, 0.0, 0.0), Quat::IDENTITY);
+	let mut history = MoveHistory::default();
+	history.resting_pose.replace(a);
+	history.record(32);
+	history.resting_pose.replace(b);
+	assert!(history.undo());
+	history.update_animation(0.5);
+
+	// the content never made it back to a, so there's nowhere new to undo to
+	history.record(32);
+	assert!(history.animation.is_none());
+	assert_eq!(history.resting_pose, None);
+	assert!(history.undo.is_empty());
+	assert!(history.redo.is_empty());
+	// and it isn't waiting on a resting pose to record either
+	history.rested(a, 32);
+	assert!(history.undo.is_empty());
+}
+
+#[test]
+fn smooth_damp_converges_without_overshoot() {
+	let target = vec3(1.0, 0.0, 0.0);
+	let mut velocity = Vec3::ZERO;
+	let mut position = Vec3::ZERO;
+	for _ in 0..90 {
+		position = smooth_damp(position, target, &mut velocity, 0.1, 1.0 / 90.0);
+		assert!(position.x <= target.x);
+	}
+	assert!(position.distance(target) < 0.001);
+}
+
+#[test]
+fn magnet_offset_follows_scale() {
+	let closest_point = Vec3::new(0.1, 0.0, 0.05);
+	let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+	let grab_point = Vec3::new(0.3, 1.2, -0.4);
+	for scale in [0.5, 1.0, 2.0] {
+		let root = grab_point - Grabbable::magnet_offset(rotation, closest_point, scale);
+		let root_transform =
+			Mat4::from_scale_rotation_translation(Vec3::splat(scale), rotation, root);
+		// the closest point ends up right on the grab point no matter the scale
+		assert!(root_transform
+			.transform_point3(closest_point)
+			.abs_diff_eq(grab_point, 0.0001));
+	}
+}
@@ -1,23 +1,56 @@
 use crate::{
+	curve::CylinderCurve,
+	geometry::{ray_plane, Ray},
 	input_action::{DeltaSet, InputQueue, InputQueueable, SingleAction},
-	lines::{self, LineExt},
+	plane_shape::{PlaneGeometry, PlaneShape},
 	DebugSettings, VisualDebug,
 };
-use glam::{vec3, Mat4, Vec3};
-use map_range::MapRange;
+use glam::{Vec2, Vec3};
+use rustc_hash::{FxHashMap, FxHashSet};
 use stardust_xr_fusion::{
 	core::values::{
 		color::{color_space::LinearRgb, rgba_linear, Rgba},
-		Vector2, Vector3,
+		Vector2,
 	},
 	drawable::{Line, LinePoint, Lines, LinesAspect},
-	fields::{Field, FieldAspect, Shape},
+	fields::Field,
 	input::{InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeType},
-	spatial::{Spatial, SpatialAspect, SpatialRefAspect, Transform},
+	root::FrameInfo,
+	spatial::{Spatial, SpatialRefAspect, Transform},
 };
 use std::{ops::Range, sync::Arc};
 
+/// What counts as interacting with a hover plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HoverInteraction {
+	/// Pinch with hands or press `select` on everything else, interacting for as long as it's held.
+	Pinch,
+	/// Click by holding still over the plane.
+	Dwell {
+		/// How long to hold still, in seconds.
+		duration: f32,
+		/// How far the point can drift across the plane while dwelling, in meters.
+		max_movement: f32,
+	},
+	/// Click with a quick pinch (or `select`) that doesn't need to be held.
+	AirTap {
+		/// Pinches held longer than this don't click, in seconds.
+		max_duration: f32,
+		/// How far `pinch_strength` or `select` has to go, lower is easier.
+		threshold: f32,
+	},
+	/// Interact by pushing a hand or tip towards the plane, for as long as it stays pushed in.
+	///
+	/// Slowly moving closer doesn't count, only a quick push.
+	///
+	/// Pointers still use `select`.
+	PushForward {
+		/// How far to push in from where the hand was resting, in meters.
+		distance: f32,
+	},
+}
+
 #[derive(Debug, Clone)]
 pub struct HoverPlaneSettings {
 	pub distance_range: Range<f32>,
@@ -27,6 +60,10 @@ pub struct HoverPlaneSettings {
 	pub line_end_thickness: f32,
 	pub line_end_color_hover: Rgba<f32, LinearRgb>,
 	pub line_end_color_interact: Rgba<f32, LinearRgb>,
+	/// The shape inside the plane's size that can be hovered over.
+	pub shape: PlaneShape,
+	/// How hovering inputs start interacting.
+	pub interaction: HoverInteraction,
 }
 impl Default for HoverPlaneSettings {
 	fn default() -> Self {
@@ -38,6 +75,8 @@ impl Default for HoverPlaneSettings {
 			line_end_thickness: 0.005,
 			line_end_color_hover: rgba_linear!(1.0, 1.0, 1.0, 0.0),
 			line_end_color_interact: rgba_linear!(0.0, 1.0, 0.75, 0.0),
+			shape: PlaneShape::Rectangle,
+			interaction: HoverInteraction::Pinch,
 		}
 	}
 }
@@ -45,15 +84,15 @@ impl Default for HoverPlaneSettings {
 pub struct HoverPlane {
 	root: Spatial,
 	input: InputQueue,
-	field: Field,
+	geometry: PlaneGeometry,
 	interact: SingleAction,
-	size: Vector2<f32>,
 	pub x_range: Range<f32>,
 	pub y_range: Range<f32>,
-	thickness: f32,
 	settings: HoverPlaneSettings,
+	interaction_states: FxHashMap<u64, InteractionState>,
 	lines: Lines,
-	debug_lines: Option<Lines>,
+	/// The points the lines were drawn to last and whether they're interacting
+	line_points: Option<Vec<(Vec3, bool)>>,
 }
 impl HoverPlane {
 	pub fn create(
@@ -65,45 +104,69 @@ impl HoverPlane {
 		y_range: Range<f32>,
 		settings: HoverPlaneSettings,
 	) -> Result<Self, NodeError> {
-		let size = size.into();
 		let root = Spatial::create(parent, transform, false)?;
-		let field = Field::create(
-			&root,
-			Transform::from_translation([0.0, 0.0, thickness * -0.5]),
-			Shape::Box([size.x, size.y, thickness].into()),
-		)?;
-		let input = InputHandler::create(&root, Transform::none(), &field)?.queue()?;
+		let geometry =
+			PlaneGeometry::create(&root, size.into(), thickness, None, settings.shape.clone())?;
+		Self::from_geometry(root, geometry, x_range, y_range, settings)
+	}
+	/// A hover plane bent around a cylinder, `x_range` maps along the curve.
+	///
+	/// The field is a box around the whole arc, see [`CylinderCurve`] for how far in front of the surface it reaches.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_curved(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		height: f32,
+		thickness: f32,
+		curve: CylinderCurve,
+		x_range: Range<f32>,
+		y_range: Range<f32>,
+		settings: HoverPlaneSettings,
+	) -> Result<Self, NodeError> {
+		let root = Spatial::create(parent, transform, false)?;
+		let geometry =
+			PlaneGeometry::create_curved(&root, height, thickness, curve, settings.shape.clone())?;
+		Self::from_geometry(root, geometry, x_range, y_range, settings)
+	}
+	fn from_geometry(
+		root: Spatial,
+		mut geometry: PlaneGeometry,
+		x_range: Range<f32>,
+		y_range: Range<f32>,
+		settings: HoverPlaneSettings,
+	) -> Result<Self, NodeError> {
+		let input = InputHandler::create(&root, Transform::none(), geometry.field())?.queue()?;
 
 		let interact_action = SingleAction::default();
+		// where hovering starts and stops
+		geometry.set_debug_guides(
+			[settings.distance_range.start, settings.distance_range.end]
+				.into_iter()
+				.filter(|depth| *depth < f32::MAX)
+				.collect(),
+		);
 
 		let lines = Lines::create(&root, Transform::identity(), &[])?;
 		Ok(HoverPlane {
 			root,
 			input,
-			field,
+			geometry,
 			interact: interact_action,
-			size,
 			x_range,
 			y_range,
-			thickness,
 			settings,
+			interaction_states: FxHashMap::default(),
 			lines,
-			debug_lines: None,
+			line_points: None,
 		})
 	}
 
-	fn hover(size: Vector2<f32>, point: Vector3<f32>, front: bool) -> bool {
-		point.x.abs() * 2.0 < size.x
-			&& point.y.abs() * 2.0 < size.y
-			&& point.z.is_sign_positive() == front
-	}
 	pub fn interact_point_local(input: &InputData) -> Vec3 {
 		match &input.input {
 			InputDataType::Pointer(p) => {
-				let normal = vec3(0.0, 0.0, 1.0);
-				let denom = normal.dot(p.direction().into());
-				let t = -Vec3::from(p.origin).dot(normal) / denom;
-				Vec3::from(p.origin) + Vec3::from(p.direction()) * t
+				let ray = Ray::new(p.origin, p.direction());
+				// pointing away from the plane, so the closest we can get is right under the pointer
+				ray_plane(ray, Vec3::ZERO, Vec3::Z).map_or(ray.origin, |t| ray.at(t))
 			}
 			InputDataType::Hand(h) => {
 				(Vec3::from(h.index.tip.position) + Vec3::from(h.thumb.tip.position)) * 0.5
@@ -111,19 +174,19 @@ impl HoverPlane {
 			InputDataType::Tip(t) => t.origin.into(),
 		}
 	}
+	/// The interact point relative to the flat (unrolled) plane
+	fn interact_point_flat(geometry: &PlaneGeometry, input: &InputData) -> Vec3 {
+		match &input.input {
+			InputDataType::Pointer(p) => geometry.ray_point(p.origin, p.direction()),
+			_ => geometry.flatten(Self::interact_point_local(input)),
+		}
+	}
 	pub fn interact_point(&self, input: &InputData) -> (Vector2<f32>, f32) {
-		let interact_point = Self::interact_point_local(input);
-
-		let x = interact_point
-			.x
-			.clamp(-self.size.x / 2.0, self.size.x / 2.0)
-			.map_range(-self.size.x / 2.0..self.size.x / 2.0, self.x_range.clone());
-		let y = interact_point
-			.y
-			.clamp(-self.size.y / 2.0, self.size.y / 2.0)
-			.map_range(self.size.y / 2.0..-self.size.y / 2.0, self.y_range.clone());
-
-		([x, y].into(), interact_point.z)
+		self.geometry.map_point(
+			Self::interact_point_flat(&self.geometry, input),
+			&self.x_range,
+			&self.y_range,
+		)
 	}
 	pub fn input_to_points<'a>(
 		&self,
@@ -139,23 +202,26 @@ impl HoverPlane {
 		&self.input
 	}
 	pub fn field(&self) -> &Field {
-		&self.field
+		self.geometry.field()
+	}
+
+	pub fn curve(&self) -> Option<CylinderCurve> {
+		self.geometry.curve()
 	}
 
+	/// On curved planes the width is along the curve, so this changes the arc.
 	pub fn set_size(&mut self, size: impl Into<Vector2<f32>>) -> Result<(), NodeError> {
-		let size = size.into();
-		self.size = size;
-		self.field
-			.set_shape(Shape::Box([size.x, size.y, self.thickness].into()))?;
-		Ok(())
+		self.line_points = None;
+		self.geometry.set_size(size.into())
 	}
 	pub fn set_thickness(&mut self, thickness: f32) -> Result<(), NodeError> {
-		self.thickness = thickness;
-		self.field
-			.set_local_transform(Transform::from_translation([0.0, 0.0, thickness * -0.5]))?;
-		self.field
-			.set_shape(Shape::Box([self.size.x, self.size.y, thickness].into()))?;
-		Ok(())
+		self.line_points = None;
+		self.geometry.set_thickness(thickness)
+	}
+	/// Bend the plane around a cylinder, or flatten it with `None`. This changes the width to match the curve.
+	pub fn set_curve(&mut self, curve: Option<CylinderCurve>) -> Result<(), NodeError> {
+		self.line_points = None;
+		self.geometry.set_curve(curve)
 	}
 
 	/// Get all the raw inputs that are hovering
@@ -178,59 +244,130 @@ impl HoverPlane {
 	}
 
 	/// Update the state of this touch plane. Run once every frame.
-	pub fn update(&mut self) {
+	pub fn update(&mut self, info: &FrameInfo) {
+		let interacting = self.update_interaction(info.delta);
 		self.interact.update(
 			false,
 			&self.input,
 			|input| match &input.input {
-				InputDataType::Pointer(_) => input.distance <= 0.0,
+				InputDataType::Pointer(_) => self.geometry.pointer_hit(input),
 				_ => {
-					let interact_point = Self::interact_point_local(input);
-					self.settings
-						.distance_range
-						.contains(&interact_point.z.abs())
-						&& Self::hover(self.size, interact_point.into(), true)
+					let interact_point = Self::interact_point_flat(&self.geometry, input);
+					interact_point.z.is_sign_positive()
+						&& self
+							.settings
+							.distance_range
+							.contains(&interact_point.z.abs())
+						&& self.geometry.contains(interact_point)
 				}
 			},
-			|input| match &input.input {
-				InputDataType::Hand(_) => input
-					.datamap
-					.with_data(|d| d.idx("pinch_strength").as_f32() > 0.95),
-				_ => input.datamap.with_data(|d| d.idx("select").as_f32() > 0.9),
-			},
+			|input| interacting.contains(&input.id),
 		);
 
-		let mut hovered_lines = self
+		let hovering = self.hovering().current().iter().map(|i| (i, false));
+		let line_points = hovering
+			.chain(self.interact.actor().map(|i| (i, true)))
+			.filter(|(i, _)| !matches!(i.input, InputDataType::Pointer(_)))
+			.map(|(i, interacting)| (Self::interact_point_local(i), interacting))
+			.collect::<Vec<_>>();
+		if self.line_points.as_ref() != Some(&line_points) {
+			let lines = line_points
+				.iter()
+				.map(|(point, interacting)| self.line_from_point(*point, *interacting))
+				.collect::<Vec<_>>();
+			self.lines.set_lines(&lines).unwrap();
+			self.line_points.replace(line_points);
+		}
+
+		let hover_points = self
 			.hovering()
 			.current()
 			.iter()
-			.filter_map(|i| self.line_from_input(i, false))
+			.map(|i| Self::interact_point_flat(&self.geometry, i))
 			.collect::<Vec<_>>();
-		if let Some(input) = self.interact.actor().cloned() {
-			if let Some(line) = self.line_from_input(&input, true) {
-				hovered_lines.push(line);
+		let interact_point = self
+			.interact
+			.actor()
+			.map(|i| Self::interact_point_flat(&self.geometry, i));
+		self.geometry
+			.update_debug_points(hover_points, interact_point);
+	}
+
+	/// Which inputs should be interacting this frame, depending on the interaction mode
+	fn update_interaction(&mut self, delta: f32) -> FxHashSet<u64> {
+		let inputs = self.input.input();
+		self.interaction_states
+			.retain(|id, _| inputs.keys().any(|i| i.id == *id));
+
+		let mut interacting = FxHashSet::default();
+		for input in inputs.keys() {
+			let hovering = self.hovering().current().contains(input)
+				|| self.interact.actor().is_some_and(|a| a.id == input.id);
+			let point = Self::interact_point_flat(&self.geometry, input);
+			let state = self.interaction_states.entry(input.id).or_default();
+
+			let acting = match self.settings.interaction {
+				HoverInteraction::Pinch => Self::pressed(input, 0.95, 0.9),
+				HoverInteraction::Dwell {
+					duration,
+					max_movement,
+				} => {
+					state.dwell(hovering, point.truncate(), duration, max_movement, delta);
+					state.clicking(input.captured, delta)
+				}
+				HoverInteraction::AirTap {
+					max_duration,
+					threshold,
+				} => {
+					let pressed = Self::pressed(input, threshold, threshold);
+					state.air_tap(hovering, pressed, max_duration, delta);
+					state.clicking(input.captured, delta)
+				}
+				HoverInteraction::PushForward { distance } => match &input.input {
+					InputDataType::Pointer(_) => Self::pressed(input, 0.95, 0.9),
+					_ => state.push_forward(hovering, point.z, distance, delta),
+				},
+			};
+			if acting {
+				interacting.insert(input.id);
 			}
 		}
-		self.lines.set_lines(&hovered_lines).unwrap();
+		interacting
+	}
+	fn pressed(input: &InputData, pinch_threshold: f32, select_threshold: f32) -> bool {
+		match &input.input {
+			InputDataType::Hand(_) => input
+				.datamap
+				.with_data(|d| d.idx("pinch_strength").as_f32() > pinch_threshold),
+			_ => input
+				.datamap
+				.with_data(|d| d.idx("select").as_f32() > select_threshold),
+		}
 	}
 
-	fn line_from_input(&self, input: &InputData, interacting: bool) -> Option<Line> {
-		if let InputDataType::Pointer(_) = &input.input {
-			None
-		} else {
-			Some(self.line_from_point(Self::interact_point_local(input), interacting))
+	/// How far along dwelling to click this input is, from 0 to 1. None if it isn't dwelling.
+	pub fn dwell_progress(&self, input: &InputData) -> Option<f32> {
+		let HoverInteraction::Dwell { duration, .. } = self.settings.interaction else {
+			return None;
+		};
+		let state = self.interaction_states.get(&input.id)?;
+		if state.dwell_clicked {
+			return None;
 		}
+		let (_, time) = state.dwell?;
+		Some((time / duration).clamp(0.0, 1.0))
 	}
+
 	fn line_from_point(&self, point: Vec3, interacting: bool) -> Line {
+		let start = self.geometry.curve_point(
+			self.geometry
+				.clamp(self.geometry.flatten(point))
+				.extend(0.0),
+		);
 		Line {
 			points: vec![
 				LinePoint {
-					point: [
-						point.x.clamp(self.size.x * -0.5, self.size.x * 0.5),
-						point.y.clamp(self.size.y * -0.5, self.size.y * 0.5),
-						0.0,
-					]
-					.into(),
+					point: start.into(),
 					thickness: self.settings.line_start_thickness,
 					color: if interacting {
 						self.settings.line_start_color_interact
@@ -252,34 +389,160 @@ impl HoverPlane {
 		}
 	}
 }
+
+#[derive(Debug, Default)]
+struct InteractionState {
+	/// How long the current pinch has been held, for air taps
+	pinch_time: Option<f32>,
+	/// Where the point started holding still and how long it's been there
+	dwell: Option<(Vec2, f32)>,
+	/// Already clicked by dwelling here, so it has to move away before clicking again
+	dwell_clicked: bool,
+	/// How far from the plane the point rests when it isn't pushing in
+	rest_depth: Option<f32>,
+	pushing: bool,
+	/// How long the current click has been held
+	click_time: Option<f32>,
+}
+impl InteractionState {
+	/// Click once `point` has stayed within `max_movement` for `duration` seconds
+	fn dwell(&mut self, hovering: bool, point: Vec2, duration: f32, max_movement: f32, delta: f32) {
+		self.dwell = match self.dwell {
+			_ if !hovering => {
+				self.dwell_clicked = false;
+				None
+			}
+			Some((start, time)) if start.distance(point) <= max_movement => {
+				let time = time + delta;
+				if !self.dwell_clicked && time >= duration {
+					self.dwell_clicked = true;
+					self.click_time.get_or_insert(0.0);
+				}
+				Some((start, time))
+			}
+			_ => {
+				self.dwell_clicked = false;
+				Some((point, 0.0))
+			}
+		};
+	}
+	/// Click when a pinch that lasted at most `max_duration` lets go over the plane
+	fn air_tap(&mut self, hovering: bool, pressed: bool, max_duration: f32, delta: f32) {
+		self.pinch_time = match (pressed, self.pinch_time) {
+			(true, time) => Some(time.map_or(0.0, |time| time + delta)),
+			(false, Some(time)) => {
+				if hovering && time <= max_duration {
+					self.click_time.get_or_insert(0.0);
+				}
+				None
+			}
+			(false, None) => None,
+		};
+	}
+	/// Interact while `depth` is pushed `distance` in from where the point rests
+	fn push_forward(&mut self, hovering: bool, depth: f32, distance: f32, delta: f32) -> bool {
+		let rest_depth = self.rest_depth.get_or_insert(depth);
+		if !self.pushing {
+			*rest_depth = if !hovering || depth > *rest_depth {
+				// follow the point straight back out so only pushing in counts
+				depth
+			} else {
+				// and slowly in, so approaching the plane doesn't count as a push
+				let follow = 1.0 - (-Self::REST_FOLLOW_SPEED * delta).exp();
+				*rest_depth + (depth - *rest_depth) * follow
+			};
+		}
+		let pushed = *rest_depth - depth;
+		let was_pushing = self.pushing;
+		// a bit of leeway so it doesn't flicker right at the threshold
+		self.pushing = if was_pushing {
+			pushed > distance * 0.5
+		} else {
+			hovering && pushed >= distance
+		};
+		if was_pushing && !self.pushing {
+			*rest_depth = depth;
+		}
+		self.pushing
+	}
+	/// How quickly the rest depth catches up with a point moving in, higher needs quicker pushes
+	const REST_FOLLOW_SPEED: f32 = 4.0;
+
+	/// Keep a click held until the input gets captured so it actually registers, then let go
+	fn clicking(&mut self, captured: bool, delta: f32) -> bool {
+		let Some(time) = self.click_time else {
+			return false;
+		};
+		let time = time + delta;
+		self.click_time = (!captured && time <= Self::CLICK_TIMEOUT).then_some(time);
+		true
+	}
+	const CLICK_TIMEOUT: f32 = 0.25;
+}
+
 impl VisualDebug for HoverPlane {
 	fn set_debug(&mut self, settings: Option<DebugSettings>) {
-		self.debug_lines = settings.and_then(|settings| {
-			let line_front = lines::rounded_rectangle(
-				self.size.x,
-				self.size.y,
-				settings.line_thickness * 0.5,
-				4,
-			)
-			.thickness(settings.line_thickness)
-			.color(settings.line_color);
-			let line_back = line_front
-				.clone()
-				.color(rgba_linear!(
-					settings.line_color.c.r,
-					settings.line_color.c.g,
-					settings.line_color.c.b,
-					settings.line_color.a * 0.5
-				))
-				.transform(Mat4::from_translation(vec3(0.0, 0.0, -self.thickness)));
+		self.geometry.set_debug(&self.root, settings);
+	}
+}
 
-			let lines = Lines::create(
-				&self.root,
-				Transform::from_translation([0.0, 0.0, 0.0]),
-				&[line_front, line_back],
-			)
-			.ok()?;
-			Some(lines)
-		})
+#[test]
+fn hover_plane_dwell() {
+	let mut state = InteractionState::default();
+	let dwell = |state: &mut InteractionState, point: Vec2| {
+		state.dwell(true, point, 0.5, 0.01, 0.125);
+		state.clicking(false, 0.125)
+	};
+	let clicks = (0..10).filter(|_| dwell(&mut state, Vec2::ZERO)).count();
+	// clicks once after half a second and holds it until it times out
+	assert_eq!(clicks, 3);
+
+	// drifting a little is fine, moving away starts over
+	assert!(!dwell(&mut state, Vec2::new(0.005, 0.0)));
+	assert!(!dwell(&mut state, Vec2::new(0.05, 0.0)));
+	assert_eq!(state.dwell, Some((Vec2::new(0.05, 0.0), 0.0)));
+	assert!(!state.dwell_clicked);
+}
+
+#[test]
+fn hover_plane_air_tap() {
+	let mut state = InteractionState::default();
+	let tap = |state: &mut InteractionState, frames: usize, hovering: bool| {
+		for _ in 0..frames {
+			state.air_tap(hovering, true, 0.3, 0.1);
+		}
+		state.air_tap(hovering, false, 0.3, 0.1);
+		state.clicking(false, 0.1)
+	};
+	assert!(tap(&mut state, 2, true));
+	// the click lets go once the input gets captured
+	assert!(state.clicking(true, 0.1));
+	assert!(!state.clicking(false, 0.1));
+
+	// held too long or let go away from the plane
+	assert!(!tap(&mut state, 10, true));
+	assert!(!tap(&mut state, 2, false));
+}
+
+#[test]
+fn hover_plane_push_forward() {
+	let mut state = InteractionState::default();
+	// slowly approaching the plane at 2cm/s never counts as a push
+	let mut depth = 0.1;
+	for _ in 0..200 {
+		assert!(!state.push_forward(true, depth, 0.02, 0.02));
+		depth -= 0.02 * 0.02;
 	}
+
+	// pushing 3cm in over 0.1 seconds does
+	let pushed = (0..5)
+		.map(|_| {
+			depth -= 0.006;
+			state.push_forward(true, depth, 0.02, 0.02)
+		})
+		.last();
+	assert_eq!(pushed, Some(true));
+
+	// and pulling back out lets go
+	assert!(!state.push_forward(true, depth + 0.03, 0.02, 0.02));
 }
@@ -67,4 +67,11 @@ impl SingleAction {
 	pub fn actor(&self) -> Option<&Arc<InputData>> {
 		self.actor.as_ref()
 	}
+
+	/// Act as if `actor` is already interacting, for tests without an input method
+	#[cfg(test)]
+	pub(crate) fn set_actor(&mut self, actor: Arc<InputData>) {
+		self.actor.replace(actor);
+		self.actor_acting = true;
+	}
 }
@@ -1,7 +1,11 @@
 pub mod button;
+pub mod curve;
 pub mod data;
+pub mod dock;
 mod dummy;
 mod exposure;
+pub mod geometry;
+pub mod gesture;
 mod grabbable;
 pub mod hover_plane;
 pub mod input_action;
@@ -9,6 +13,10 @@ pub mod keyboard;
 pub mod lines;
 pub mod mouse;
 pub mod multi;
+pub mod panel_bridge;
+pub mod plane_shape;
+pub mod scroll_view;
+pub mod slider;
 pub mod state_machine;
 pub mod touch_plane;
 
@@ -21,12 +29,15 @@ use stardust_xr_fusion::core::values::color::{color_space::LinearRgb, rgba_linea
 pub struct DebugSettings {
 	pub line_thickness: f32,
 	pub line_color: Rgba<f32, LinearRgb>,
+	/// How often debug visuals that have to ask the server (e.g. bounding boxes) are refreshed, in seconds.
+	pub refresh_interval: f32,
 }
 impl Default for DebugSettings {
 	fn default() -> Self {
 		Self {
 			line_thickness: 0.002,
 			line_color: rgba_linear!(0.14, 0.62, 1.0, 1.0),
+			refresh_interval: 0.5,
 		}
 	}
 }
@@ -14,7 +14,10 @@ use std::f32::consts::{PI, TAU};
 pub trait LineExt: Sized {
 	fn thickness(self, thickness: f32) -> Self;
 	fn color(self, color: Rgba<f32, LinearRgb>) -> Self;
-	// fn trace(self, amount: f32) -> Self;
+	/// Cut the line off at `amount` (0 to 1) of the way along its length, e.g. to animate it drawing in.
+	///
+	/// Cyclic lines get traced all the way back around to their first point.
+	fn trace(self, amount: f32) -> Self;
 	fn lerp(self, other: &Self, amount: f32) -> Option<Self>;
 	fn transform(self, transform: impl Into<Matrix4>) -> Self;
 }
@@ -49,63 +52,55 @@ impl LineExt for Line {
 		}
 	}
 
-	// fn trace(self, t: f32) -> Self {
-	// 	let points =
-	// 	if t <= 0.0 {
-	// 		return self;
-	// 	}
-	// 	if t >= 1.0 || points.len() < 2 {
-	// 		return self;
-	// 	}
-	// 	let first_point = points.first().unwrap().clone();
-	// 	if cyclic {
-	// 		points.push(first_point.clone());
-	// 	}
-	// 	let mut segment_start_t = 0.0;
-	// 	let mut segment_start_point = first_point.clone();
-	// 	let mut segment_end_t = 0.0;
-	// 	let mut segment_end_point = first_point.clone();
-
-	// 	let mut new_length: usize = 0;
-	// 	{
-	// 		let mut current_t = 0.0;
-	// 		let mut previous_point = &first_point;
-	// 		for (points_len, point) in points.iter().enumerate() {
-	// 			let previous_position: Vec3 = previous_point.point.into();
-	// 			let previous_t = current_t;
-	// 			current_t += previous_position.distance(point.point.into());
-	// 			if current_t > t {
-	// 				new_length = points_len;
-	// 				segment_start_t = previous_t;
-	// 				segment_end_t = current_t;
-	// 				segment_start_point = previous_point.clone();
-	// 				segment_end_point = point.clone();
-	// 				break;
-	// 			}
-	// 			previous_point = point;
-	// 		}
-	// 	}
-
-	// 	points.truncate(new_length);
-	// 	let last = points.last_mut().unwrap();
-
-	// 	let segment_t = (t - segment_start_t) / (segment_end_t - segment_start_t);
-	// 	last.color = segment_start_point
-	// 		.color
-	// 		.mix(segment_end_point.color, segment_t);
-	// 	last.thickness = (segment_start_point.thickness * segment_t)
-	// 		+ (segment_end_point.thickness * (1.0 - segment_t));
-	// 	last.point = Vector3::from([
-	// 		(segment_start_point.point.x * segment_t)
-	// 			+ (segment_end_point.point.x * (1.0 - segment_t)),
-	// 		(segment_start_point.point.y * segment_t)
-	// 			+ (segment_end_point.point.y * (1.0 - segment_t)),
-	// 		(segment_start_point.point.z * segment_t)
-	// 			+ (segment_end_point.point.z * (1.0 - segment_t)),
-	// 	]);
+	fn trace(self, amount: f32) -> Self {
+		if amount >= 1.0 || self.points.len() < 2 {
+			return self;
+		}
+		let mut points = self.points;
+		if amount <= 0.0 {
+			points.clear();
+			return Line {
+				points,
+				cyclic: false,
+			};
+		}
+		if self.cyclic {
+			points.push(points[0].clone());
+		}
 
-	// 	points
-	// }
+		let length: f32 = points
+			.windows(2)
+			.map(|p| Vec3::from(p[0].point).distance(p[1].point.into()))
+			.sum();
+		let target = length * amount;
+		let mut traveled = 0.0;
+		for i in 1..points.len() {
+			let (from, to) = (&points[i - 1], &points[i]);
+			let segment_length = Vec3::from(from.point).distance(to.point.into());
+			if traveled + segment_length >= target {
+				let t = if segment_length > 0.0 {
+					(target - traveled) / segment_length
+				} else {
+					0.0
+				};
+				let end = LinePoint {
+					point: Vec3::from(from.point)
+						.lerp_bounded(Vec3::from(to.point), t)
+						.into(),
+					thickness: from.thickness.lerp_bounded(to.thickness, t),
+					color: from.color.lerp_bounded(to.color, t),
+				};
+				points.truncate(i);
+				points.push(end);
+				break;
+			}
+			traveled += segment_length;
+		}
+		Line {
+			points,
+			cyclic: false,
+		}
+	}
 
 	fn lerp(self, to: &Self, amount: f32) -> Option<Self> {
 		if self.points.len() != to.points.len() {
@@ -248,6 +243,30 @@ pub fn line_from_points(points: Vec<impl Into<Vector3<f32>>>) -> Line {
 	}
 }
 
+/// `color` with its alpha multiplied by `alpha`, for lines that should sit behind the main ones.
+pub fn faded(color: Rgba<f32, LinearRgb>, alpha: f32) -> Rgba<f32, LinearRgb> {
+	rgba_linear!(color.c.r, color.c.g, color.c.b, color.a * alpha)
+}
+
+/// A small ring on the z=0 plane under `point`, with a line up to the point itself.
+pub(crate) fn debug_point(
+	point: impl Into<Vec3>,
+	thickness: f32,
+	color: Rgba<f32, LinearRgb>,
+) -> [Line; 2] {
+	let point = point.into();
+	let projected = vec3(point.x, point.y, 0.0);
+	[
+		circle(8, 0.0, thickness * 2.0)
+			.thickness(thickness)
+			.color(color)
+			.transform(Mat4::from_translation(projected)),
+		line_from_points(vec![projected, point])
+			.thickness(thickness)
+			.color(color),
+	]
+}
+
 pub fn axes(length: f32, thickness: f32) -> Vec<Line> {
 	let r = rgba_linear!(1.0, 0.0, 0.0, 1.0);
 	let g = rgba_linear!(0.0, 1.0, 0.0, 1.0);
@@ -306,6 +325,18 @@ pub fn bounding_box(bounding_box: BoundingBox) -> Vec<Line> {
 			(center + vec3(size_half.x, size_half.y, -size_half.z)).into(),
 			(center + vec3(size_half.x, -size_half.y, -size_half.z)).into(),
 		],
+		vec![
+			(center + vec3(size_half.x, -size_half.y, size_half.z)).into(),
+			(center + vec3(size_half.x, -size_half.y, -size_half.z)).into(),
+		],
+		vec![
+			(center + vec3(-size_half.x, size_half.y, size_half.z)).into(),
+			(center + vec3(-size_half.x, -size_half.y, size_half.z)).into(),
+		],
+		vec![
+			(center + vec3(-size_half.x, size_half.y, -size_half.z)).into(),
+			(center + vec3(-size_half.x, -size_half.y, -size_half.z)).into(),
+		],
 	];
 
 	lines_points
@@ -322,3 +353,30 @@ pub fn bounding_box(bounding_box: BoundingBox) -> Vec<Line> {
 		})
 		.collect()
 }
+
+#[test]
+fn trace_cuts_at_length() {
+	let line = line_from_points(vec![
+		vec3(0.0, 0.0, 0.0),
+		vec3(1.0, 0.0, 0.0),
+		vec3(1.0, 3.0, 0.0),
+	]);
+	let traced = line.clone().trace(0.5);
+	assert_eq!(traced.points.len(), 3);
+	assert!(Vec3::from(traced.points[2].point).abs_diff_eq(vec3(1.0, 1.0, 0.0), 0.0001));
+	assert_eq!(line.clone().trace(1.0).points.len(), 3);
+	assert!(line.trace(0.0).points.is_empty());
+
+	// cyclic lines go back around to the start
+	let mut square = line_from_points(vec![
+		vec3(0.0, 0.0, 0.0),
+		vec3(1.0, 0.0, 0.0),
+		vec3(1.0, 1.0, 0.0),
+		vec3(0.0, 1.0, 0.0),
+	]);
+	square.cyclic = true;
+	let traced = square.trace(0.875);
+	assert!(!traced.cyclic);
+	assert_eq!(traced.points.len(), 5);
+	assert!(Vec3::from(traced.points[4].point).abs_diff_eq(vec3(0.0, 0.5, 0.0), 0.0001));
+}
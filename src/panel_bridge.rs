@@ -0,0 +1,254 @@
+use crate::{
+	hover_plane::{HoverPlane, HoverPlaneSettings},
+	touch_plane::{ContactId, TouchPlane, TouchPlaneSettings},
+	DebugSettings, VisualDebug,
+};
+use glam::{vec2, Vec2};
+use rustc_hash::FxHashMap;
+use stardust_xr_fusion::{
+	core::values::Vector2,
+	input::InputDataType,
+	items::panel::{PanelItem, PanelItemAspect, SurfaceId},
+	node::{NodeError, NodeType},
+	root::FrameInfo,
+	spatial::{SpatialRefAspect, Transform},
+};
+
+/// Makes a panel item surface touchable by putting a `TouchPlane` over it and forwarding touches as panel touch events.
+///
+/// Every contact is its own touch, so enabling more fingers in the touch plane's settings allows multi-finger gestures.
+pub struct PanelTouchBridge {
+	touch_plane: TouchPlane,
+	panel: PanelItem,
+	surface: SurfaceId,
+	/// Touch plane contact to panel touch id and where it was last sent
+	touches: FxHashMap<ContactId, (u32, Vector2<f32>)>,
+	next_touch_id: u32,
+}
+impl PanelTouchBridge {
+	/// `size` is in meters, `surface_size` is in pixels.
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		size: impl Into<Vector2<f32>>,
+		surface_size: impl Into<Vector2<u32>>,
+		panel: &PanelItem,
+		surface: SurfaceId,
+	) -> Result<Self, NodeError> {
+		let surface_size = surface_size.into();
+		let touch_plane = TouchPlane::create(
+			parent,
+			transform,
+			size,
+			0.01,
+			0.0..surface_size.x as f32,
+			0.0..surface_size.y as f32,
+			TouchPlaneSettings::default(),
+		)?;
+		Ok(PanelTouchBridge {
+			touch_plane,
+			panel: panel.alias(),
+			surface,
+			touches: FxHashMap::default(),
+			next_touch_id: 0,
+		})
+	}
+
+	/// Update the touch plane and send touch events to the panel. Run once every frame.
+	pub fn update(&mut self) -> Result<(), NodeError> {
+		self.touch_plane.update();
+		for contact in self.touch_plane.contacts().removed() {
+			let Some((touch_id, _)) = self.touches.remove(contact) else {
+				continue;
+			};
+			self.panel.touch_up(touch_id)?;
+		}
+		for contact in self.touch_plane.current_contacts() {
+			let position = contact.point;
+			if let Some((touch_id, last_position)) = self.touches.get_mut(&contact.id) {
+				if *last_position != position {
+					self.panel.touch_move(*touch_id, position)?;
+					*last_position = position;
+				}
+			} else {
+				let touch_id = self.next_touch_id;
+				self.next_touch_id = self.next_touch_id.wrapping_add(1);
+				self.panel
+					.touch_down(self.surface.clone(), touch_id, position)?;
+				self.touches.insert(contact.id, (touch_id, position));
+			}
+		}
+		Ok(())
+	}
+
+	/// Lift every touch that's currently down, e.g. before hiding the panel.
+	pub fn release_all(&mut self) -> Result<(), NodeError> {
+		for (_, (touch_id, _)) in self.touches.drain() {
+			self.panel.touch_up(touch_id)?;
+		}
+		Ok(())
+	}
+
+	/// Change which surface touches go to, lifting any current touches.
+	pub fn set_surface(&mut self, surface: SurfaceId) -> Result<(), NodeError> {
+		self.release_all()?;
+		self.surface = surface;
+		Ok(())
+	}
+	/// Call when the surface is resized so touches keep landing on the right pixels.
+	pub fn set_surface_size(&mut self, surface_size: impl Into<Vector2<u32>>) {
+		let surface_size = surface_size.into();
+		self.touch_plane.x_range = 0.0..surface_size.x as f32;
+		self.touch_plane.y_range = 0.0..surface_size.y as f32;
+	}
+
+	pub fn touch_plane(&self) -> &TouchPlane {
+		&self.touch_plane
+	}
+	pub fn touch_plane_mut(&mut self) -> &mut TouchPlane {
+		&mut self.touch_plane
+	}
+}
+impl VisualDebug for PanelTouchBridge {
+	fn set_debug(&mut self, settings: Option<DebugSettings>) {
+		self.touch_plane.set_debug(settings)
+	}
+}
+
+/// Linux input event code for the left mouse button
+const BTN_LEFT: u32 = 0x110;
+
+/// Makes a panel item surface usable from a distance by putting a `HoverPlane` (pinchscreen) over it.
+///
+/// Hovering moves the panel's pointer, pinching (or `select` on pointers) holds down the left button
+/// and pointer scrolling scrolls the panel.
+pub struct PanelPointerBridge {
+	hover_plane: HoverPlane,
+	panel: PanelItem,
+	surface: SurfaceId,
+	pressed: bool,
+	position: Option<Vector2<f32>>,
+	/// The input moving the pointer while nothing is pinching, followed until it stops hovering
+	hover_input: Option<u64>,
+}
+impl PanelPointerBridge {
+	/// `size` is in meters, `surface_size` is in pixels.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		size: impl Into<Vector2<f32>>,
+		thickness: f32,
+		surface_size: impl Into<Vector2<u32>>,
+		panel: &PanelItem,
+		surface: SurfaceId,
+		settings: HoverPlaneSettings,
+	) -> Result<Self, NodeError> {
+		let surface_size = surface_size.into();
+		let hover_plane = HoverPlane::create(
+			parent,
+			transform,
+			size,
+			thickness,
+			0.0..surface_size.x as f32,
+			0.0..surface_size.y as f32,
+			settings,
+		)?;
+		Ok(PanelPointerBridge {
+			hover_plane,
+			panel: panel.alias(),
+			surface,
+			pressed: false,
+			position: None,
+			hover_input: None,
+		})
+	}
+
+	/// Update the hover plane and send pointer events to the panel. Run once every frame.
+	pub fn update(&mut self, info: &FrameInfo) -> Result<(), NodeError> {
+		self.hover_plane.update(info);
+		let interact = self.hover_plane.interact_status();
+
+		// keep following the same hovering input until it leaves
+		let hovering = self.hover_plane.hovering().current();
+		let hover_input = self
+			.hover_input
+			.and_then(|id| hovering.iter().find(|i| i.id == id))
+			.or_else(|| hovering.iter().next());
+		self.hover_input = hover_input.map(|i| i.id);
+		// the pinching input always wins so drags don't jump to other hands
+		let position = interact
+			.actor()
+			.or(hover_input)
+			.map(|input| self.hover_plane.interact_point(input).0);
+		if let Some(position) = position {
+			if self.position != Some(position) {
+				self.panel.pointer_motion(self.surface.clone(), position)?;
+				self.position.replace(position);
+			}
+		}
+
+		let pressed = interact.actor_acting();
+		if pressed != self.pressed {
+			self.panel
+				.pointer_button(self.surface.clone(), BTN_LEFT, pressed)?;
+			self.pressed = pressed;
+		}
+
+		let (scroll_distance, scroll_steps) = self
+			.hover_plane
+			.hovering()
+			.current()
+			.iter()
+			.chain(interact.actor())
+			.filter(|i| matches!(i.input, InputDataType::Pointer(_)))
+			.map(|i| {
+				i.datamap.with_data(|d| {
+					let continuous = d.idx("scroll_continuous").as_vector();
+					let discrete = d.idx("scroll_discrete").as_vector();
+					(
+						vec2(continuous.idx(0).as_f32(), continuous.idx(1).as_f32()),
+						vec2(discrete.idx(0).as_f32(), discrete.idx(1).as_f32()),
+					)
+				})
+			})
+			.fold((Vec2::ZERO, Vec2::ZERO), |(distance, steps), (d, s)| {
+				(distance + d, steps + s)
+			});
+		if scroll_distance != Vec2::ZERO || scroll_steps != Vec2::ZERO {
+			self.panel
+				.pointer_scroll(self.surface.clone(), scroll_distance, scroll_steps)?;
+		}
+		Ok(())
+	}
+
+	/// Change which surface pointer events go to, letting go of the button if it's held.
+	pub fn set_surface(&mut self, surface: SurfaceId) -> Result<(), NodeError> {
+		if self.pressed {
+			self.panel
+				.pointer_button(self.surface.clone(), BTN_LEFT, false)?;
+			self.pressed = false;
+		}
+		self.surface = surface;
+		self.position.take();
+		Ok(())
+	}
+	/// Call when the surface is resized so the pointer keeps landing on the right pixels.
+	pub fn set_surface_size(&mut self, surface_size: impl Into<Vector2<u32>>) {
+		let surface_size = surface_size.into();
+		self.hover_plane.x_range = 0.0..surface_size.x as f32;
+		self.hover_plane.y_range = 0.0..surface_size.y as f32;
+	}
+
+	pub fn hover_plane(&self) -> &HoverPlane {
+		&self.hover_plane
+	}
+	pub fn hover_plane_mut(&mut self) -> &mut HoverPlane {
+		&mut self.hover_plane
+	}
+}
+impl VisualDebug for PanelPointerBridge {
+	fn set_debug(&mut self, settings: Option<DebugSettings>) {
+		self.hover_plane.set_debug(settings)
+	}
+}
@@ -0,0 +1,454 @@
+use crate::{
+	curve::CylinderCurve,
+	geometry::{closest_point_on_segment, ray_plane, Ray},
+	lines::{self, line_from_points, LineExt},
+	DebugSettings,
+};
+use glam::{vec3, Mat4, Quat, Vec2, Vec3};
+use map_range::MapRange;
+use stardust_xr_fusion::{
+	core::values::Vector2,
+	drawable::{Line, Lines, LinesAspect},
+	fields::{CylinderShape, Field, FieldAspect, Shape},
+	input::{InputData, InputDataType},
+	node::NodeError,
+	spatial::{SpatialAspect, SpatialRefAspect, Transform},
+};
+use std::ops::Range;
+
+/// The outline of a `TouchPlane` or `HoverPlane` inside its size.
+///
+/// Everything is relative to the center of the plane, in meters.
+///
+/// The plane's field only follows `Rectangle` and `Circle` on flat planes,
+/// everything else gets a box around the whole size, and curved planes get a box around the whole arc
+/// (see [`CylinderCurve`]). Inputs are still checked against the shape itself,
+/// but the field's distance (e.g. for pointers) is to the box rather than the shape's edge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PlaneShape {
+	#[default]
+	Rectangle,
+	/// A circle, or an ellipse if the plane isn't square.
+	Circle,
+	RoundedRectangle {
+		corner_radius: f32,
+	},
+	/// Any simple polygon, points should stay inside the plane's size.
+	Polygon(Vec<Vector2<f32>>),
+}
+impl PlaneShape {
+	/// Is the point inside the shape?
+	pub fn contains(&self, size: Vector2<f32>, point: impl Into<Vec2>) -> bool {
+		let point = point.into();
+		let half_size = Vec2::from(size) * 0.5;
+		if point.x.abs() > half_size.x || point.y.abs() > half_size.y {
+			return false;
+		}
+		match self {
+			PlaneShape::Rectangle => true,
+			PlaneShape::Circle => (point / half_size).length_squared() <= 1.0,
+			PlaneShape::RoundedRectangle { corner_radius } => {
+				let (corner_center, radius) = Self::corner(half_size, *corner_radius);
+				(point.abs() - corner_center).cmple(Vec2::ZERO).any()
+					|| point.abs().distance(corner_center) <= radius
+			}
+			PlaneShape::Polygon(points) => {
+				// count how many edges a ray going right from the point crosses
+				let mut inside = false;
+				for (a, b) in Self::edges(points) {
+					if (a.y > point.y) != (b.y > point.y)
+						&& point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+					{
+						inside = !inside;
+					}
+				}
+				inside
+			}
+		}
+	}
+
+	/// The closest point inside the shape.
+	pub fn clamp(&self, size: Vector2<f32>, point: impl Into<Vec2>) -> Vec2 {
+		let point = point.into();
+		if self.contains(size, point) {
+			return point;
+		}
+		let half_size = Vec2::from(size) * 0.5;
+		match self {
+			PlaneShape::Rectangle => point.clamp(-half_size, half_size),
+			// not exactly the closest point on an ellipse, but close enough for inputs hovering just outside
+			PlaneShape::Circle => (point / half_size).normalize_or_zero() * half_size,
+			PlaneShape::RoundedRectangle { corner_radius } => {
+				let point = point.clamp(-half_size, half_size);
+				let (corner_center, radius) = Self::corner(half_size, *corner_radius);
+				let in_corner = (point.abs() - corner_center).cmpgt(Vec2::ZERO).all();
+				if !in_corner {
+					return point;
+				}
+				let from_center = (point.abs() - corner_center).normalize_or_zero() * radius;
+				(corner_center + from_center) * point.signum()
+			}
+			PlaneShape::Polygon(points) => Self::edges(points)
+				.map(|(a, b)| {
+					closest_point_on_segment(a.extend(0.0), b.extend(0.0), point.extend(0.0))
+						.truncate()
+				})
+				.min_by(|a, b| {
+					a.distance_squared(point)
+						.total_cmp(&b.distance_squared(point))
+				})
+				.unwrap_or(point),
+		}
+	}
+
+	/// A line around the edge of the shape.
+	pub fn outline(&self, size: Vector2<f32>) -> Line {
+		let half_size = Vec2::from(size) * 0.5;
+		match self {
+			PlaneShape::Rectangle => {
+				let mut line = line_from_points(vec![
+					vec3(half_size.x, half_size.y, 0.0),
+					vec3(-half_size.x, half_size.y, 0.0),
+					vec3(-half_size.x, -half_size.y, 0.0),
+					vec3(half_size.x, -half_size.y, 0.0),
+				]);
+				line.cyclic = true;
+				line
+			}
+			PlaneShape::Circle => {
+				lines::circle(64, 0.0, 1.0).transform(Mat4::from_scale(half_size.extend(1.0)))
+			}
+			PlaneShape::RoundedRectangle { corner_radius } => lines::rounded_rectangle(
+				size.x,
+				size.y,
+				corner_radius.min(half_size.x).min(half_size.y),
+				8,
+			),
+			PlaneShape::Polygon(points) => {
+				let mut line = line_from_points(
+					points
+						.iter()
+						.map(|p| vec3(p.x, p.y, 0.0))
+						.collect::<Vec<_>>(),
+				);
+				line.cyclic = true;
+				line
+			}
+		}
+	}
+
+	/// The center and radius of the top right rounded corner
+	fn corner(half_size: Vec2, corner_radius: f32) -> (Vec2, f32) {
+		let radius = corner_radius.clamp(0.0, half_size.x.min(half_size.y));
+		(half_size - Vec2::splat(radius), radius)
+	}
+	fn edges(points: &[Vector2<f32>]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+		points
+			.iter()
+			.zip(points.iter().cycle().skip(1))
+			.map(|(a, b)| (Vec2::from(*a), Vec2::from(*b)))
+	}
+}
+
+/// The size, curve and shape shared by `TouchPlane` and `HoverPlane`, along with the field covering them.
+///
+/// Points are relative to the flat (unrolled) plane unless they say otherwise.
+pub(crate) struct PlaneGeometry {
+	size: Vector2<f32>,
+	thickness: f32,
+	curve: Option<CylinderCurve>,
+	shape: PlaneShape,
+	field: Field,
+
+	debug_settings: Option<DebugSettings>,
+	/// Depths of extra faded outlines, e.g. where hovering starts and stops
+	debug_guides: Vec<f32>,
+	/// Rebuilt whenever the geometry changes, the live points get drawn on top
+	debug_outline: Vec<Line>,
+	/// The points drawn last time and whether they're active, None when the lines need redrawing
+	debug_points: Option<Vec<(Vec3, bool)>>,
+	debug_lines: Option<Lines>,
+}
+impl PlaneGeometry {
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		size: Vector2<f32>,
+		thickness: f32,
+		curve: Option<CylinderCurve>,
+		shape: PlaneShape,
+	) -> Result<Self, NodeError> {
+		let (transform, field_shape) = Self::field_shape(size, thickness, curve, &shape);
+		let field = Field::create(parent, transform, field_shape)?;
+		Ok(PlaneGeometry {
+			size,
+			thickness,
+			curve,
+			shape,
+			field,
+
+			debug_settings: None,
+			debug_guides: Vec::new(),
+			debug_outline: Vec::new(),
+			debug_points: None,
+			debug_lines: None,
+		})
+	}
+	/// Bent around `curve`, with the width along the curve.
+	pub fn create_curved(
+		parent: &impl SpatialRefAspect,
+		height: f32,
+		thickness: f32,
+		curve: CylinderCurve,
+		shape: PlaneShape,
+	) -> Result<Self, NodeError> {
+		let size = [curve.width(), height].into();
+		Self::create(parent, size, thickness, Some(curve), shape)
+	}
+
+	fn field_shape(
+		size: Vector2<f32>,
+		thickness: f32,
+		curve: Option<CylinderCurve>,
+		shape: &PlaneShape,
+	) -> (Transform, Shape) {
+		match (curve, shape) {
+			(Some(curve), _) => curve.field(size.y, thickness),
+			// scaled into an elliptic cylinder, so distances are only exact for circles
+			(None, PlaneShape::Circle) => (
+				Transform::from_translation_rotation_scale(
+					[0.0, 0.0, thickness * -0.5],
+					Quat::IDENTITY,
+					[size.x, size.y, 1.0],
+				),
+				Shape::Cylinder(CylinderShape {
+					length: thickness,
+					radius: 0.5,
+				}),
+			),
+			(None, _) => (
+				Transform::from_translation([0.0, 0.0, thickness * -0.5]),
+				Shape::Box([size.x, size.y, thickness].into()),
+			),
+		}
+	}
+	fn update_field(&mut self) -> Result<(), NodeError> {
+		let (transform, shape) =
+			Self::field_shape(self.size, self.thickness, self.curve, &self.shape);
+		self.field.set_local_transform(transform)?;
+		self.field.set_shape(shape)?;
+		self.update_debug_outline();
+		Ok(())
+	}
+
+	pub fn size(&self) -> Vector2<f32> {
+		self.size
+	}
+	pub fn thickness(&self) -> f32 {
+		self.thickness
+	}
+	pub fn curve(&self) -> Option<CylinderCurve> {
+		self.curve
+	}
+	pub fn shape(&self) -> &PlaneShape {
+		&self.shape
+	}
+	pub fn field(&self) -> &Field {
+		&self.field
+	}
+
+	/// On curved planes the width is along the curve, so this changes the arc.
+	pub fn set_size(&mut self, size: Vector2<f32>) -> Result<(), NodeError> {
+		self.size = size;
+		if let Some(curve) = &mut self.curve {
+			curve.arc = size.x / curve.radius;
+		}
+		self.update_field()
+	}
+	pub fn set_thickness(&mut self, thickness: f32) -> Result<(), NodeError> {
+		self.thickness = thickness;
+		self.update_field()
+	}
+	/// Bend around a cylinder, or flatten with `None`. This changes the width to match the curve.
+	pub fn set_curve(&mut self, curve: Option<CylinderCurve>) -> Result<(), NodeError> {
+		if let Some(curve) = curve {
+			self.size.x = curve.width();
+		}
+		self.curve = curve;
+		self.update_field()
+	}
+	pub fn set_shape(&mut self, shape: PlaneShape) -> Result<(), NodeError> {
+		self.shape = shape;
+		self.update_field()
+	}
+
+	/// Unroll a point relative to the plane onto the flat plane.
+	pub fn flatten(&self, point: impl Into<Vec3>) -> Vec3 {
+		let point = point.into();
+		self.curve.map_or(point, |curve| curve.flatten(point))
+	}
+	/// Bend a point on the flat plane back around the curve.
+	pub fn curve_point(&self, point: impl Into<Vec3>) -> Vec3 {
+		let point = point.into();
+		self.curve.map_or(point, |curve| curve.curve(point))
+	}
+	/// Bend a line drawn on the flat plane around the curve.
+	pub fn curve_line(&self, line: Line) -> Line {
+		match self.curve {
+			Some(curve) => curve.curve_line(line),
+			None => line,
+		}
+	}
+
+	/// Show the outline and live points under `root`, or hide them with `None`.
+	pub fn set_debug(&mut self, root: &impl SpatialRefAspect, settings: Option<DebugSettings>) {
+		self.debug_settings = settings;
+		self.debug_lines =
+			settings.and_then(|_| Lines::create(root, Transform::identity(), &[]).ok());
+		self.update_debug_outline();
+	}
+	/// Draw extra faded outlines this far in front of the surface.
+	pub fn set_debug_guides(&mut self, depths: Vec<f32>) {
+		self.debug_guides = depths;
+		self.update_debug_outline();
+	}
+	fn update_debug_outline(&mut self) {
+		self.debug_points = None;
+		let Some(settings) = self.debug_settings else {
+			self.debug_outline.clear();
+			return;
+		};
+		let outline = self.shape.outline(self.size);
+		let offset_outline = |z: f32| {
+			outline
+				.clone()
+				.transform(Mat4::from_translation(vec3(0.0, 0.0, z)))
+		};
+		let back = offset_outline(-self.thickness).color(lines::faded(settings.line_color, 0.5));
+		let guides = self
+			.debug_guides
+			.iter()
+			.map(|z| offset_outline(*z).color(lines::faded(settings.line_color, 0.25)));
+		self.debug_outline = [outline.clone().color(settings.line_color), back]
+			.into_iter()
+			.chain(guides)
+			.map(|line| self.curve_line(line).thickness(settings.line_thickness))
+			.collect();
+	}
+	/// Draw the flat points of hovering and active (touching or interacting) inputs over the outline.
+	pub fn update_debug_points(
+		&mut self,
+		hovering: impl IntoIterator<Item = Vec3>,
+		active: impl IntoIterator<Item = Vec3>,
+	) {
+		let (Some(settings), Some(debug_lines)) = (&self.debug_settings, &self.debug_lines) else {
+			return;
+		};
+		let points = hovering
+			.into_iter()
+			.map(|point| (point, false))
+			.chain(active.into_iter().map(|point| (point, true)))
+			.collect::<Vec<_>>();
+		if self.debug_points.as_ref() == Some(&points) {
+			return;
+		}
+		let point_lines = points.iter().flat_map(|(point, active)| {
+			let color = if *active {
+				settings.line_color
+			} else {
+				lines::faded(settings.line_color, 0.5)
+			};
+			lines::debug_point(*point, settings.line_thickness, color)
+				.map(|line| self.curve_line(line))
+		});
+		let lines = self
+			.debug_outline
+			.iter()
+			.cloned()
+			.chain(point_lines)
+			.collect::<Vec<_>>();
+		let _ = debug_lines.set_lines(&lines);
+		self.debug_points.replace(points);
+	}
+
+	/// Is the flat point over the shape?
+	pub fn contains(&self, point: impl Into<Vec3>) -> bool {
+		self.shape.contains(self.size, point.into().truncate())
+	}
+	/// The closest point inside the shape, on the surface.
+	pub fn clamp(&self, point: impl Into<Vec3>) -> Vec2 {
+		self.shape.clamp(self.size, point.into().truncate())
+	}
+	/// Map a flat point into `x_range` and `y_range`, along with how far in front of the surface it is.
+	pub fn map_point(
+		&self,
+		point: impl Into<Vec3>,
+		x_range: &Range<f32>,
+		y_range: &Range<f32>,
+	) -> (Vector2<f32>, f32) {
+		let point = point.into();
+		let clamped = self.clamp(point);
+		let x = clamped
+			.x
+			.map_range(-self.size.x / 2.0..self.size.x / 2.0, x_range.clone());
+		let y = clamped
+			.y
+			.map_range(self.size.y / 2.0..-self.size.y / 2.0, y_range.clone());
+		([x, y].into(), point.z)
+	}
+
+	/// Where a ray hits the flat plane, or right under its origin if it points away.
+	pub fn ray_point(&self, origin: impl Into<Vec3>, direction: impl Into<Vec3>) -> Vec3 {
+		let ray = Ray::new(origin, direction);
+		match self.curve {
+			Some(curve) => curve.flatten(
+				curve
+					.ray_intersect(ray.origin, ray.direction)
+					.unwrap_or(ray.origin),
+			),
+			None => ray_plane(ray, Vec3::ZERO, Vec3::Z).map_or(ray.origin, |t| ray.at(t)),
+		}
+	}
+	/// Is this a pointer pointing at the shape?
+	pub fn pointer_hit(&self, input: &InputData) -> bool {
+		let InputDataType::Pointer(p) = &input.input else {
+			return false;
+		};
+		// the field is only a box around the shape, so check the shape itself
+		let hit = match self.curve {
+			Some(curve) => curve.ray_intersect(p.origin, p.direction()).is_some(),
+			None => input.distance <= 0.0,
+		};
+		hit && self.contains(self.ray_point(p.origin, p.direction()))
+	}
+}
+
+#[test]
+fn plane_shape_contains_and_clamp() {
+	use glam::vec2;
+	let size = Vector2::from([2.0, 2.0]);
+
+	assert!(PlaneShape::Circle.contains(size, vec2(0.5, 0.5)));
+	assert!(!PlaneShape::Circle.contains(size, vec2(0.9, 0.9)));
+	assert!(PlaneShape::Circle
+		.clamp(size, vec2(2.0, 0.0))
+		.abs_diff_eq(vec2(1.0, 0.0), 0.0001));
+
+	let rounded = PlaneShape::RoundedRectangle { corner_radius: 0.5 };
+	assert!(rounded.contains(size, vec2(0.95, 0.0)));
+	assert!(!rounded.contains(size, vec2(0.95, 0.95)));
+	assert!(rounded.clamp(size, vec2(2.0, 2.0)).abs_diff_eq(
+		Vec2::splat(0.5 + 0.5 * std::f32::consts::FRAC_1_SQRT_2),
+		0.0001
+	));
+
+	let triangle = PlaneShape::Polygon(vec![
+		[-1.0, -1.0].into(),
+		[1.0, -1.0].into(),
+		[0.0, 1.0].into(),
+	]);
+	assert!(triangle.contains(size, vec2(0.0, 0.0)));
+	assert!(!triangle.contains(size, vec2(0.9, 0.9)));
+	assert!(triangle
+		.clamp(size, vec2(0.0, -1.5))
+		.abs_diff_eq(vec2(0.0, -1.0), 0.0001));
+}
@@ -0,0 +1,333 @@
+use crate::{hover_plane::HoverPlane, touch_plane::TouchPlane};
+use glam::{vec2, Vec2};
+use stardust_xr_fusion::{
+	core::values::Vector2,
+	input::{InputData, InputDataType},
+	node::NodeError,
+	root::FrameInfo,
+	spatial::{Spatial, SpatialAspect, SpatialRefAspect, Transform},
+};
+use std::{ops::Range, sync::Arc};
+
+#[derive(Debug, Clone)]
+pub struct ScrollViewSettings {
+	/// How far the content can be scrolled horizontally, in meters.
+	pub x_range: Range<f32>,
+	/// How far the content can be scrolled vertically, in meters.
+	pub y_range: Range<f32>,
+	/// Drag (unity style) for momentum after letting go.
+	pub drag: f32,
+	/// How much of a drag past the edges actually moves the content.
+	pub overscroll_resistance: f32,
+	/// How quickly overscrolled content springs back, higher is faster.
+	pub rubber_band_stiffness: f32,
+	/// Meters scrolled per unit of pointer `scroll_continuous`.
+	pub scroll_continuous_speed: f32,
+	/// Meters scrolled per pointer `scroll_discrete` step.
+	pub scroll_discrete_speed: f32,
+}
+impl Default for ScrollViewSettings {
+	fn default() -> Self {
+		Self {
+			x_range: 0.0..0.0,
+			y_range: 0.0..1.0,
+			drag: 4.0,
+			overscroll_resistance: 0.35,
+			rubber_band_stiffness: 12.0,
+			scroll_continuous_speed: 0.001,
+			scroll_discrete_speed: 0.02,
+		}
+	}
+}
+
+/// Scrolls a content spatial with drags and pointer scroll from a `TouchPlane` or `HoverPlane`.
+///
+/// The plane's `x_range` and `y_range` should be in meters with y going up so drags line up with the content.
+pub struct ScrollView {
+	content: Spatial,
+	settings: ScrollViewSettings,
+	state: ScrollState,
+	applied_offset: Option<Vec2>,
+}
+impl ScrollView {
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		settings: ScrollViewSettings,
+	) -> Result<Self, NodeError> {
+		let content = Spatial::create(parent, Transform::identity(), false)?;
+		Ok(ScrollView {
+			content,
+			settings,
+			state: ScrollState::default(),
+			applied_offset: None,
+		})
+	}
+
+	/// Scroll using a touch plane's interactions. Run once every frame after updating the touch plane.
+	pub fn update_touch_plane(
+		&mut self,
+		info: &FrameInfo,
+		touch_plane: &TouchPlane,
+	) -> Result<(), NodeError> {
+		let action = touch_plane.action();
+		let touching = action.interact().current();
+		// keep following the input that started the drag until it lets go
+		let dragging = self
+			.state
+			.drag
+			.and_then(|(id, _)| touching.iter().find(|i| i.id == id))
+			.or_else(|| touching.iter().next())
+			.map(|i| (i.id, Vec2::from(touch_plane.interact_point(i).0)));
+		let scroll = self.pointer_scroll(
+			action
+				.hover()
+				.current()
+				.iter()
+				.chain(action.interact().current()),
+		);
+		self.update(info, dragging, scroll)
+	}
+	/// Scroll using a hover plane's interactions. Run once every frame after updating the hover plane.
+	pub fn update_hover_plane(
+		&mut self,
+		info: &FrameInfo,
+		hover_plane: &HoverPlane,
+	) -> Result<(), NodeError> {
+		let dragging = hover_plane
+			.interact_status()
+			.actor()
+			.map(|i| (i.id, Vec2::from(hover_plane.interact_point(i).0)));
+		let scroll = self.pointer_scroll(hover_plane.hovering().current().iter());
+		self.update(info, dragging, scroll)
+	}
+
+	/// Total pointer scroll this frame, in meters
+	fn pointer_scroll<'a>(&self, inputs: impl Iterator<Item = &'a Arc<InputData>>) -> Vec2 {
+		inputs
+			.filter(|i| matches!(i.input, InputDataType::Pointer(_)))
+			.map(|i| {
+				i.datamap.with_data(|d| {
+					let continuous = d.idx("scroll_continuous").as_vector();
+					let discrete = d.idx("scroll_discrete").as_vector();
+					vec2(continuous.idx(0).as_f32(), continuous.idx(1).as_f32())
+						* self.settings.scroll_continuous_speed
+						+ vec2(discrete.idx(0).as_f32(), discrete.idx(1).as_f32())
+							* self.settings.scroll_discrete_speed
+				})
+			})
+			.sum()
+	}
+
+	fn update(
+		&mut self,
+		info: &FrameInfo,
+		dragging: Option<(u64, Vec2)>,
+		scroll: Vec2,
+	) -> Result<(), NodeError> {
+		self.state
+			.step(&self.settings, info.delta, dragging, scroll);
+
+		let offset = self.state.offset;
+		if self.applied_offset != Some(offset) {
+			self.content
+				.set_local_transform(Transform::from_translation([offset.x, offset.y, 0.0]))?;
+			self.applied_offset.replace(offset);
+		}
+		Ok(())
+	}
+
+	/// The spatial to put scrolled content under.
+	pub fn content(&self) -> &Spatial {
+		&self.content
+	}
+	/// How far the content has been scrolled, in meters.
+	pub fn offset(&self) -> Vector2<f32> {
+		self.state.offset.into()
+	}
+	pub fn set_offset(&mut self, offset: impl Into<Vector2<f32>>) {
+		self.state.offset = Vec2::from(offset.into());
+		self.state.velocity = Vec2::ZERO;
+	}
+	pub fn velocity(&self) -> Vector2<f32> {
+		self.state.velocity.into()
+	}
+	/// Change how far the content can scroll, e.g. when the content changes size.
+	pub fn set_scroll_range(&mut self, x_range: Range<f32>, y_range: Range<f32>) {
+		self.settings.x_range = x_range;
+		self.settings.y_range = y_range;
+	}
+}
+
+/// The scroll offset and momentum, kept apart from the content so it can be stepped without a server.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct ScrollState {
+	offset: Vec2,
+	velocity: Vec2,
+	/// The input dragging and where it was last frame
+	drag: Option<(u64, Vec2)>,
+}
+impl ScrollState {
+	const VELOCITY_STOP_THRESHOLD: f32 = 0.00001;
+
+	/// Advance by `delta` seconds given the input dragging (if any) and the pointer scroll this frame.
+	fn step(
+		&mut self,
+		settings: &ScrollViewSettings,
+		delta: f32,
+		dragging: Option<(u64, Vec2)>,
+		scroll: Vec2,
+	) {
+		match (dragging, self.drag) {
+			(Some((id, point)), Some((last_id, last_point))) if id == last_id => {
+				let mut movement = point - last_point;
+				// resist dragging past the edges
+				let overscroll = self.overscroll(settings);
+				if overscroll.x != 0.0 {
+					movement.x *= settings.overscroll_resistance;
+				}
+				if overscroll.y != 0.0 {
+					movement.y *= settings.overscroll_resistance;
+				}
+				self.offset += movement;
+				if delta > 0.0 {
+					self.velocity = self.velocity.lerp(movement / delta, 0.5);
+				}
+			}
+			// just started dragging, so catch the content
+			(Some(_), _) => self.velocity = Vec2::ZERO,
+			(None, _) => {
+				self.offset += self.velocity * delta;
+				self.velocity *= (1.0 - settings.drag * delta).clamp(0.0, 1.0);
+				if self.velocity.length_squared() < Self::VELOCITY_STOP_THRESHOLD {
+					self.velocity = Vec2::ZERO;
+				}
+
+				// rubber band back inside the range
+				let overscroll = self.overscroll(settings);
+				if overscroll.x != 0.0 {
+					self.velocity.x = 0.0;
+				}
+				if overscroll.y != 0.0 {
+					self.velocity.y = 0.0;
+				}
+				let spring = 1.0 - (-settings.rubber_band_stiffness * delta).exp();
+				self.offset -= overscroll * spring;
+			}
+		}
+		self.drag = dragging;
+
+		if scroll != Vec2::ZERO {
+			// positive scroll is down/right, which moves the content up/left
+			self.offset += vec2(-scroll.x, scroll.y);
+			self.offset -= self.overscroll(settings);
+		}
+	}
+
+	/// How far past the scroll range the offset is on each axis
+	fn overscroll(&self, settings: &ScrollViewSettings) -> Vec2 {
+		let clamp = |value: f32, range: &Range<f32>| {
+			value.clamp(range.start.min(range.end), range.start.max(range.end))
+		};
+		let clamped = vec2(
+			clamp(self.offset.x, &settings.x_range),
+			clamp(self.offset.y, &settings.y_range),
+		);
+		self.offset - clamped
+	}
+}
+
+#[test]
+fn scroll_view_momentum() {
+	let settings = ScrollViewSettings {
+		x_range: -10.0..10.0,
+		y_range: -10.0..10.0,
+		..Default::default()
+	};
+	let mut state = ScrollState::default();
+	state.step(&settings, 0.1, Some((0, Vec2::ZERO)), Vec2::ZERO);
+	assert_eq!(state.velocity, Vec2::ZERO);
+	state.step(&settings, 0.1, Some((0, vec2(0.0, 0.1))), Vec2::ZERO);
+	assert_eq!(state.offset, vec2(0.0, 0.1));
+	assert!(state.velocity.y > 0.0);
+
+	// keeps going after letting go, slowing down until it stops
+	let mut last_velocity = state.velocity.y;
+	let mut last_offset = state.offset.y;
+	for _ in 0..5 {
+		state.step(&settings, 0.1, None, Vec2::ZERO);
+		assert!(state.offset.y > last_offset);
+		assert!(state.velocity.y < last_velocity);
+		last_velocity = state.velocity.y;
+		last_offset = state.offset.y;
+	}
+	for _ in 0..1000 {
+		state.step(&settings, 0.1, None, Vec2::ZERO);
+	}
+	assert_eq!(state.velocity, Vec2::ZERO);
+
+	// grabbing again catches it
+	state.velocity = vec2(0.0, 1.0);
+	state.step(&settings, 0.1, Some((1, Vec2::ZERO)), Vec2::ZERO);
+	assert_eq!(state.velocity, Vec2::ZERO);
+}
+
+#[test]
+fn scroll_view_overscroll_resistance() {
+	let settings = ScrollViewSettings {
+		x_range: 0.0..1.0,
+		y_range: 0.0..1.0,
+		overscroll_resistance: 0.5,
+		..Default::default()
+	};
+	let mut state = ScrollState {
+		offset: vec2(0.5, 1.5),
+		..Default::default()
+	};
+	state.step(&settings, 0.1, Some((0, Vec2::ZERO)), Vec2::ZERO);
+	state.step(&settings, 0.1, Some((0, vec2(0.2, 0.2))), Vec2::ZERO);
+	// only the overscrolled axis is resisted
+	assert!((state.offset - vec2(0.7, 1.6)).length() < 0.0001);
+}
+
+#[test]
+fn scroll_view_rubber_band() {
+	let settings = ScrollViewSettings {
+		x_range: 0.0..1.0,
+		y_range: 1.0..0.0,
+		..Default::default()
+	};
+	let mut state = ScrollState {
+		offset: vec2(-0.5, 1.5),
+		velocity: vec2(-1.0, 1.0),
+		drag: None,
+	};
+	let mut last_overscroll = state.overscroll(&settings).length();
+	for _ in 0..10 {
+		state.step(&settings, 0.1, None, Vec2::ZERO);
+		// overscrolling kills the momentum instead of pushing further out
+		assert_eq!(state.velocity, Vec2::ZERO);
+		let overscroll = state.overscroll(&settings).length();
+		assert!(overscroll < last_overscroll);
+		last_overscroll = overscroll;
+	}
+	for _ in 0..100 {
+		state.step(&settings, 0.1, None, Vec2::ZERO);
+	}
+	assert!(state.overscroll(&settings).length() < 0.0001);
+}
+
+#[test]
+fn scroll_view_pointer_scroll() {
+	let settings = ScrollViewSettings {
+		x_range: -1.0..1.0,
+		y_range: -1.0..1.0,
+		..Default::default()
+	};
+	let mut state = ScrollState::default();
+	// scrolling down/right moves the content up/left
+	state.step(&settings, 0.1, None, vec2(0.25, 0.5));
+	assert_eq!(state.offset, vec2(-0.25, 0.5));
+	// and scrolling never goes past the edges
+	state.step(&settings, 0.1, None, vec2(5.0, -5.0));
+	assert_eq!(state.offset, vec2(-1.0, -1.0));
+}
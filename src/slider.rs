@@ -0,0 +1,330 @@
+use crate::{
+	lines::{circle, line_from_points, LineExt},
+	touch_plane::{TouchPlane, TouchPlaneSettings},
+	VisualDebug,
+};
+use glam::{vec3, Mat4, Vec3};
+use map_range::MapRange;
+use stardust_xr_fusion::{
+	core::values::{
+		color::{color_space::LinearRgb, rgba_linear, Rgba},
+		Vector2,
+	},
+	drawable::{Lines, LinesAspect},
+	node::NodeError,
+	spatial::{SpatialRefAspect, Transform},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliderOrientation {
+	/// Minimum on the left, maximum on the right
+	Horizontal,
+	/// Minimum at the bottom, maximum at the top
+	Vertical,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SliderVisualSettings {
+	pub line_thickness: f32,
+	pub track_color: Rgba<f32, LinearRgb>,
+	pub accent_color: Rgba<f32, LinearRgb>,
+}
+impl Default for SliderVisualSettings {
+	fn default() -> Self {
+		Self {
+			line_thickness: 0.005,
+			track_color: rgba_linear!(1.0, 1.0, 1.0, 0.25),
+			accent_color: rgba_linear!(0.0, 1.0, 0.75, 1.0),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SliderSettings {
+	pub min: f32,
+	pub max: f32,
+	/// None means the value is continuous.
+	pub step: Option<f32>,
+	pub orientation: SliderOrientation,
+	pub visuals: Option<SliderVisualSettings>,
+}
+impl Default for SliderSettings {
+	fn default() -> Self {
+		Self {
+			min: 0.0,
+			max: 1.0,
+			step: None,
+			orientation: SliderOrientation::Horizontal,
+			visuals: Some(SliderVisualSettings::default()),
+		}
+	}
+}
+
+pub struct Slider {
+	settings: SliderSettings,
+	touch_plane: TouchPlane,
+	value: f32,
+	changed: bool,
+	/// The input that started dragging, followed until it lets go
+	drag_input: Option<u64>,
+	visuals: Option<SliderVisuals>,
+}
+impl Slider {
+	pub fn create(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		size: impl Into<Vector2<f32>>,
+		settings: SliderSettings,
+	) -> Result<Self, NodeError> {
+		let size = size.into();
+		let range = settings.min..settings.max;
+		// the touch plane maps straight to the value along the slider
+		let (x_range, y_range) = match settings.orientation {
+			SliderOrientation::Horizontal => (range, 0.0..0.0),
+			SliderOrientation::Vertical => (0.0..0.0, settings.max..settings.min),
+		};
+		let touch_plane = TouchPlane::create(
+			parent,
+			transform,
+			size,
+			0.015,
+			x_range,
+			y_range,
+			TouchPlaneSettings::default(),
+		)?;
+
+		Ok(Slider {
+			visuals: settings
+				.visuals
+				.map(|v| SliderVisuals::create(&touch_plane, size, v))
+				.transpose()?,
+			value: settings.min,
+			changed: false,
+			drag_input: None,
+			settings,
+			touch_plane,
+		})
+	}
+
+	/// Update the state of this slider. Run once every frame.
+	pub fn update(&mut self) {
+		self.touch_plane.update();
+		self.changed = false;
+
+		// keep following the input that started the drag so extra fingers don't make it jump
+		let touching = self.touch_plane.action().interact().current();
+		if !self
+			.drag_input
+			.is_some_and(|id| touching.iter().any(|i| i.id == id))
+		{
+			self.drag_input = touching.iter().next().map(|i| i.id);
+		}
+		let dragging = self
+			.drag_input
+			.and_then(|id| touching.iter().find(|i| i.id == id))
+			.cloned();
+		if let Some(input) = dragging {
+			let (point, _) = self.touch_plane.interact_point(&input);
+			let value = match self.settings.orientation {
+				SliderOrientation::Horizontal => point.x,
+				SliderOrientation::Vertical => point.y,
+			};
+			let value = Self::snap(&self.settings, value);
+			if value != self.value {
+				self.value = value;
+				self.changed = true;
+			}
+		}
+
+		if let Some(visuals) = &mut self.visuals {
+			visuals.update(&self.touch_plane, &self.settings, self.value);
+		}
+	}
+	fn snap(settings: &SliderSettings, value: f32) -> f32 {
+		let (min, max) = (
+			settings.min.min(settings.max),
+			settings.min.max(settings.max),
+		);
+		let value = match settings.step {
+			Some(step) if step > 0.0 => {
+				settings.min + ((value - settings.min) / step).round() * step
+			}
+			_ => value,
+		};
+		value.clamp(min, max)
+	}
+
+	pub fn touch_plane(&self) -> &TouchPlane {
+		&self.touch_plane
+	}
+
+	pub fn value(&self) -> f32 {
+		self.value
+	}
+	/// Set the value without counting as a change from the user.
+	pub fn set_value(&mut self, value: f32) {
+		self.value = Self::snap(&self.settings, value);
+	}
+	/// Did the user change the value during the last update?
+	pub fn changed(&self) -> bool {
+		self.changed
+	}
+	/// Is something currently dragging the slider?
+	pub fn dragging(&self) -> bool {
+		!self.touch_plane.action().interact().current().is_empty()
+	}
+}
+impl VisualDebug for Slider {
+	fn set_debug(&mut self, settings: Option<crate::DebugSettings>) {
+		self.touch_plane.set_debug(settings)
+	}
+}
+
+struct SliderVisuals {
+	size: Vector2<f32>,
+	visual_settings: SliderVisualSettings,
+	lines: Lines,
+	/// The value and whether it was active when the lines were last drawn
+	drawn: Option<(f32, bool)>,
+}
+impl SliderVisuals {
+	fn create(
+		touch_plane: &TouchPlane,
+		size: Vector2<f32>,
+		settings: SliderVisualSettings,
+	) -> Result<Self, NodeError> {
+		let lines = Lines::create(touch_plane.root(), Transform::identity(), &[])?;
+		Ok(SliderVisuals {
+			size,
+			visual_settings: settings,
+			lines,
+			drawn: None,
+		})
+	}
+
+	/// Where the thumb sits for `value`, from the start of the track at min to the end at max
+	fn thumb_point(settings: &SliderSettings, size: Vector2<f32>, value: f32) -> Vec3 {
+		let length = match settings.orientation {
+			SliderOrientation::Horizontal => size.x,
+			SliderOrientation::Vertical => size.y,
+		};
+		// a slider without a range has nowhere to go, so keep the thumb at the start
+		let distance = if settings.min == settings.max {
+			length * -0.5
+		} else {
+			value
+				.map_range(settings.min..settings.max, length * -0.5..length * 0.5)
+				.clamp(length * -0.5, length * 0.5)
+		};
+		match settings.orientation {
+			SliderOrientation::Horizontal => vec3(distance, 0.0, 0.0),
+			SliderOrientation::Vertical => vec3(0.0, distance, 0.0),
+		}
+	}
+
+	fn update(&mut self, touch_plane: &TouchPlane, settings: &SliderSettings, value: f32) {
+		let active = !touch_plane.action().hover().current().is_empty()
+			|| !touch_plane.action().interact().current().is_empty();
+		if self.drawn == Some((value, active)) {
+			return;
+		}
+		self.drawn.replace((value, active));
+
+		let (start, end) = (
+			Self::thumb_point(settings, self.size, settings.min),
+			Self::thumb_point(settings, self.size, settings.max),
+		);
+		let thumb = Self::thumb_point(settings, self.size, value);
+		let thumb_radius = if active {
+			self.visual_settings.line_thickness * 2.0
+		} else {
+			self.visual_settings.line_thickness * 1.25
+		};
+
+		let _ = self.lines.set_lines(&[
+			line_from_points(vec![thumb, end])
+				.thickness(self.visual_settings.line_thickness)
+				.color(self.visual_settings.track_color),
+			line_from_points(vec![start, thumb])
+				.thickness(self.visual_settings.line_thickness)
+				.color(self.visual_settings.accent_color),
+			circle(16, 0.0, thumb_radius)
+				.thickness(self.visual_settings.line_thickness)
+				.color(self.visual_settings.accent_color)
+				.transform(Mat4::from_translation(thumb)),
+		]);
+	}
+}
+
+#[test]
+fn slider_snap() {
+	let settings = SliderSettings {
+		min: 0.0,
+		max: 1.0,
+		step: Some(0.25),
+		..Default::default()
+	};
+	assert_eq!(Slider::snap(&settings, 0.3), 0.25);
+	assert_eq!(Slider::snap(&settings, 0.4), 0.5);
+	assert_eq!(Slider::snap(&settings, 1.4), 1.0);
+	assert_eq!(Slider::snap(&settings, -0.2), 0.0);
+
+	// min above max still snaps from min and clamps to the range
+	let reversed = SliderSettings {
+		min: 1.0,
+		max: 0.0,
+		..settings
+	};
+	assert_eq!(Slider::snap(&reversed, 0.3), 0.25);
+	assert_eq!(Slider::snap(&reversed, 1.5), 1.0);
+	assert_eq!(Slider::snap(&reversed, -0.5), 0.0);
+
+	let continuous = SliderSettings {
+		step: None,
+		..settings
+	};
+	assert_eq!(Slider::snap(&continuous, 0.3), 0.3);
+}
+
+#[test]
+fn slider_thumb_point() {
+	let size = Vector2::from([0.2, 0.04]);
+	let horizontal = SliderSettings {
+		min: 0.0,
+		max: 10.0,
+		..Default::default()
+	};
+	assert_eq!(
+		SliderVisuals::thumb_point(&horizontal, size, 0.0),
+		vec3(-0.1, 0.0, 0.0)
+	);
+	assert_eq!(
+		SliderVisuals::thumb_point(&horizontal, size, 5.0),
+		Vec3::ZERO
+	);
+	// out of range values stay on the track
+	assert_eq!(
+		SliderVisuals::thumb_point(&horizontal, size, 20.0),
+		vec3(0.1, 0.0, 0.0)
+	);
+
+	// vertical sliders go from the bottom up along their height
+	let vertical = SliderSettings {
+		orientation: SliderOrientation::Vertical,
+		..horizontal
+	};
+	assert_eq!(
+		SliderVisuals::thumb_point(&vertical, size, 10.0),
+		vec3(0.0, 0.02, 0.0)
+	);
+
+	// no range doesn't end up as NaN
+	let empty = SliderSettings {
+		max: 0.0,
+		..horizontal
+	};
+	assert_eq!(
+		SliderVisuals::thumb_point(&empty, size, 0.0),
+		vec3(-0.1, 0.0, 0.0)
+	);
+}
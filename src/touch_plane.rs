@@ -1,32 +1,140 @@
 use crate::{
-	input_action::{InputQueue, InputQueueable, MultiAction},
+	curve::CylinderCurve,
+	input_action::{DeltaSet, InputQueue, InputQueueable, MultiAction},
 	lines::{self, LineExt},
+	plane_shape::{PlaneGeometry, PlaneShape},
 	DebugSettings, VisualDebug,
 };
-use glam::{vec3, Mat4, Vec3};
+use glam::{Mat4, Vec3};
 use map_range::MapRange;
+use rustc_hash::FxHashMap;
 use stardust_xr_fusion::{
-	core::values::{color::rgba_linear, Vector2, Vector3},
-	drawable::Lines,
-	fields::{Field, FieldAspect, Shape},
-	input::{InputData, InputDataType, InputHandler},
+	core::values::{
+		color::{color_space::LinearRgb, rgba_linear, Rgba},
+		Vector2, Vector3,
+	},
+	drawable::{Lines, LinesAspect},
+	fields::Field,
+	input::{Hand, InputData, InputDataType, InputHandler},
 	node::{NodeError, NodeType},
-	spatial::{Spatial, SpatialAspect, SpatialRefAspect, Transform},
+	spatial::{Spatial, SpatialRefAspect, Transform},
 };
 use std::{ops::Range, sync::Arc};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fingertip {
+	Thumb,
+	Index,
+	Middle,
+	Ring,
+	Little,
+}
+impl Fingertip {
+	pub fn position(self, hand: &Hand) -> Vector3<f32> {
+		match self {
+			Fingertip::Thumb => hand.thumb.tip.position,
+			Fingertip::Index => hand.index.tip.position,
+			Fingertip::Middle => hand.middle.tip.position,
+			Fingertip::Ring => hand.ring.tip.position,
+			Fingertip::Little => hand.little.tip.position,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct TouchPlaneSettings {
+	/// Which fingertips on hands can touch, empty means hands can't touch at all.
+	pub fingers: Vec<Fingertip>,
+	/// How far past the surface a fingertip or tip has to go to count as touching.
+	pub press_depth: f32,
+	/// How far a pointer's `select` has to be pressed to count as touching.
+	pub pointer_select_threshold: f32,
+	/// Count touches coming in from behind the plane too.
+	///
+	/// Touches then only last while inside the plane's thickness,
+	/// since anything further back is treated like hovering over the back face.
+	pub back_face_entry: bool,
+	/// Draw a cursor under each input, None to leave the visuals to whatever uses the touch plane.
+	pub cursor: Option<TouchCursorSettings>,
+	/// The shape inside the plane's size that can be touched.
+	pub shape: PlaneShape,
+}
+impl Default for TouchPlaneSettings {
+	fn default() -> Self {
+		TouchPlaneSettings {
+			fingers: vec![Fingertip::Index],
+			press_depth: 0.0,
+			pointer_select_threshold: 0.5,
+			back_face_entry: false,
+			cursor: None,
+			shape: PlaneShape::Rectangle,
+		}
+	}
+}
+
+/// A ring under each hovering input that shrinks as it gets closer, becoming a dot on contact.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchCursorSettings {
+	/// Inputs further than this from the plane don't get a cursor.
+	pub max_hover_distance: f32,
+	/// Radius of the ring at `max_hover_distance`.
+	pub ring_radius: f32,
+	/// Radius of the dot on contact.
+	pub dot_radius: f32,
+	pub line_thickness: f32,
+	pub hover_color: Rgba<f32, LinearRgb>,
+	pub touch_color: Rgba<f32, LinearRgb>,
+}
+impl Default for TouchCursorSettings {
+	fn default() -> Self {
+		TouchCursorSettings {
+			max_hover_distance: 0.05,
+			ring_radius: 0.015,
+			dot_radius: 0.004,
+			line_thickness: 0.002,
+			hover_color: rgba_linear!(1.0, 1.0, 1.0, 0.75),
+			touch_color: rgba_linear!(0.0, 1.0, 0.75, 1.0),
+		}
+	}
+}
+
+/// Identifies a single point touching the plane, stable for as long as it keeps touching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContactId {
+	/// Id of the input the contact comes from.
+	pub input: u64,
+	/// Which fingertip of a hand this is, None for pointers and tips.
+	pub finger: Option<Fingertip>,
+}
+
+/// A single point touching the plane, every enabled fingertip on a hand is its own contact.
+#[derive(Debug, Clone)]
+pub struct TouchContact {
+	pub id: ContactId,
+	pub input: Arc<InputData>,
+	/// Where it's touching, in x_range and y_range.
+	pub point: Vector2<f32>,
+	/// How far in front of the plane it is, negative when pressed in.
+	pub depth: f32,
+}
+
 pub struct TouchPlane {
-	size: Vector2<f32>,
 	pub x_range: Range<f32>,
 	pub y_range: Range<f32>,
-	thickness: f32,
+	settings: TouchPlaneSettings,
 
 	root: Spatial,
 	input: InputQueue,
-	field: Field,
+	geometry: PlaneGeometry,
 	action: MultiAction,
+	/// Contacts and fingertips hovering, with their points relative to the flat plane
+	hovering: FxHashMap<ContactId, Vector3<f32>>,
+	contacts: DeltaSet<ContactId>,
+	contact_data: FxHashMap<ContactId, (TouchContact, Vector3<f32>)>,
 
-	debug_lines: Option<Lines>,
+	cursor_lines: Option<Lines>,
+	/// The points the cursors were drawn at last and whether they're touching
+	cursor_points: Option<Vec<(Vector3<f32>, bool)>>,
 }
 impl TouchPlane {
 	pub fn create(
@@ -36,76 +144,275 @@ impl TouchPlane {
 		thickness: f32,
 		x_range: Range<f32>,
 		y_range: Range<f32>,
+		settings: TouchPlaneSettings,
+	) -> Result<Self, NodeError> {
+		let root = Spatial::create(parent, transform, false)?;
+		let geometry =
+			PlaneGeometry::create(&root, size.into(), thickness, None, settings.shape.clone())?;
+		Self::from_geometry(root, geometry, x_range, y_range, settings)
+	}
+	/// A touch plane bent around a cylinder, `x_range` maps along the curve.
+	///
+	/// The field is a box around the whole arc, see [`CylinderCurve`] for how far in front of the surface it reaches.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_curved(
+		parent: &impl SpatialRefAspect,
+		transform: Transform,
+		height: f32,
+		thickness: f32,
+		curve: CylinderCurve,
+		x_range: Range<f32>,
+		y_range: Range<f32>,
+		settings: TouchPlaneSettings,
 	) -> Result<Self, NodeError> {
-		let size = size.into();
 		let root = Spatial::create(parent, transform, false)?;
-		let field = Field::create(
-			&root,
-			Transform::from_translation([0.0, 0.0, thickness * -0.5]),
-			Shape::Box([size.x, size.y, thickness].into()),
-		)?;
-		let input = InputHandler::create(&root, Transform::none(), &field)?.queue()?;
+		let geometry =
+			PlaneGeometry::create_curved(&root, height, thickness, curve, settings.shape.clone())?;
+		Self::from_geometry(root, geometry, x_range, y_range, settings)
+	}
+	fn from_geometry(
+		root: Spatial,
+		geometry: PlaneGeometry,
+		x_range: Range<f32>,
+		y_range: Range<f32>,
+		settings: TouchPlaneSettings,
+	) -> Result<Self, NodeError> {
+		let input = InputHandler::create(&root, Transform::none(), geometry.field())?.queue()?;
+		let cursor_lines = settings
+			.cursor
+			.is_some()
+			.then(|| Lines::create(&root, Transform::identity(), &[]))
+			.transpose()?;
 
 		Ok(TouchPlane {
-			size,
 			x_range,
 			y_range,
-			thickness,
+			settings,
 
 			root,
 			input,
-			field,
+			geometry,
 			action: Default::default(),
-			debug_lines: None,
+			hovering: FxHashMap::default(),
+			contacts: DeltaSet::default(),
+			contact_data: FxHashMap::default(),
+			cursor_lines,
+			cursor_points: None,
 		})
 	}
 
-	fn hover(size: Vector2<f32>, point: Vector3<f32>, front: bool) -> bool {
-		point.z.is_sign_positive() == front
-			&& point.x.abs() * 2.0 < size.x
-			&& point.y.abs() * 2.0 < size.y
+	/// Point relative to the flat (unrolled) plane
+	fn flatten(&self, point: Vector3<f32>) -> Vector3<f32> {
+		self.geometry.flatten(point).into()
+	}
+
+	fn hover(&self, point: Vector3<f32>) -> bool {
+		self.geometry.contains(point)
+			&& (point.z > -self.settings.press_depth
+				|| (self.settings.back_face_entry && point.z < -self.geometry.thickness()))
+	}
+	fn touch(&self, point: Vector3<f32>) -> bool {
+		self.geometry.contains(point)
+			&& point.z <= -self.settings.press_depth
+			&& (!self.settings.back_face_entry || point.z >= -self.geometry.thickness())
+	}
+	/// The enabled fingertip furthest into the plane, relative to the flat plane. None if no fingers are enabled.
+	fn fingertip(&self, hand: &Hand) -> Option<Vector3<f32>> {
+		self.settings
+			.fingers
+			.iter()
+			.map(|f| self.flatten(f.position(hand)))
+			.min_by(|a, b| a.z.total_cmp(&b.z))
 	}
 
 	/// Update the state of this touch plane. Run once every frame.
 	pub fn update(&mut self) {
-		self.action.update(
+		let mut action = std::mem::take(&mut self.action);
+		action.update(
 			&self.input,
 			|input| match &input.input {
-				InputDataType::Pointer(_) => input.distance < 0.0,
-				InputDataType::Hand(h) => Self::hover(self.size, h.index.tip.position, true),
-				InputDataType::Tip(t) => Self::hover(self.size, t.origin, true),
+				InputDataType::Pointer(_) => self.geometry.pointer_hit(input),
+				InputDataType::Hand(h) => self.fingertip(h).is_some_and(|p| self.hover(p)),
+				InputDataType::Tip(t) => self.hover(self.flatten(t.origin)),
 			},
 			|input| match &input.input {
-				InputDataType::Pointer(_) => {
-					input.datamap.with_data(|d| d.idx("select").as_f32() > 0.5)
-				}
-				InputDataType::Hand(h) => Self::hover(self.size, h.index.tip.position, false),
-				InputDataType::Tip(t) => Self::hover(self.size, t.origin, false),
+				InputDataType::Pointer(_) => input.datamap.with_data(|d| {
+					d.idx("select").as_f32() > self.settings.pointer_select_threshold
+				}),
+				InputDataType::Hand(h) => self.fingertip(h).is_some_and(|p| self.touch(p)),
+				InputDataType::Tip(t) => self.touch(self.flatten(t.origin)),
 			},
 		);
+		self.action = action;
+		self.update_contacts();
+		self.update_cursors();
+		self.geometry.update_debug_points(
+			self.hovering.values().map(|point| Vec3::from(*point)),
+			self.contact_data
+				.values()
+				.map(|(_, point)| Vec3::from(*point)),
+		);
 	}
-	pub fn interact_point(&self, input: &InputData) -> (Vector2<f32>, f32) {
-		let interact_point = match &input.input {
-			InputDataType::Pointer(p) => {
-				let normal = vec3(0.0, 0.0, 1.0);
-				let denom = normal.dot(p.direction().into());
-				let t = -Vec3::from(p.origin).dot(normal) / denom;
-				(Vec3::from(p.origin) + Vec3::from(p.direction()) * t).into()
+	fn update_contacts(&mut self) {
+		let mut hovering = FxHashMap::default();
+		let mut contacts = FxHashMap::default();
+		for input in self.action.hover().current() {
+			match &input.input {
+				InputDataType::Hand(h) => hovering.extend(
+					self.fingertips(input.id, h)
+						.filter(|(_, point)| self.hover(*point)),
+				),
+				_ => {
+					hovering.insert(
+						ContactId {
+							input: input.id,
+							finger: None,
+						},
+						self.interact_point_flat(input),
+					);
+				}
 			}
-			InputDataType::Hand(h) => h.index.tip.position,
-			InputDataType::Tip(t) => t.origin,
+		}
+		for input in self.action.interact().current() {
+			let InputDataType::Hand(h) = &input.input else {
+				let id = ContactId {
+					input: input.id,
+					finger: None,
+				};
+				let point = self.interact_point_flat(input);
+				contacts.insert(id, (self.contact(id, input, point), point));
+				continue;
+			};
+			let entering = self
+				.action
+				.interact()
+				.added()
+				.iter()
+				.any(|i| i.id == input.id);
+			let fingers = self
+				.fingertips(input.id, h)
+				.map(|(id, point)| (id, point, self.touch(point)))
+				.collect::<Vec<_>>();
+			let touching = Self::hand_contacts(&fingers, entering, |id| {
+				self.contacts.current().contains(id) || self.hovering.contains_key(id)
+			});
+			for (id, point, _) in fingers {
+				if touching.contains(&id) {
+					contacts.insert(id, (self.contact(id, input, point), point));
+				} else if self.hover(point) {
+					hovering.insert(id, point);
+				}
+			}
+		}
+		self.hovering = hovering;
+		self.contacts.push_new(contacts.keys().copied());
+		self.contact_data = contacts;
+	}
+	/// Which of a touching hand's fingers (id, flat point, touching) are contacts.
+	///
+	/// Fingers only count once they press in from the front, so they have to already be `known` as a contact or hovering.
+	/// The deepest finger counts straight away when the hand starts `entering`,
+	/// since hands aren't hovering in the frame between starting to touch and getting captured.
+	fn hand_contacts(
+		fingers: &[(ContactId, Vector3<f32>, bool)],
+		entering: bool,
+		known: impl Fn(&ContactId) -> bool,
+	) -> Vec<ContactId> {
+		let deepest = fingers
+			.iter()
+			.min_by(|a, b| a.1.z.total_cmp(&b.1.z))
+			.map(|(id, _, _)| *id);
+		fingers
+			.iter()
+			.filter(|(id, _, touching)| {
+				*touching && (known(id) || (entering && Some(*id) == deepest))
+			})
+			.map(|(id, _, _)| *id)
+			.collect()
+	}
+	fn fingertips<'a>(
+		&'a self,
+		input: u64,
+		hand: &'a Hand,
+	) -> impl Iterator<Item = (ContactId, Vector3<f32>)> + 'a {
+		self.settings.fingers.iter().map(move |finger| {
+			(
+				ContactId {
+					input,
+					finger: Some(*finger),
+				},
+				self.flatten(finger.position(hand)),
+			)
+		})
+	}
+	fn contact(&self, id: ContactId, input: &Arc<InputData>, point: Vector3<f32>) -> TouchContact {
+		let (point, depth) = self.map_point(point);
+		TouchContact {
+			id,
+			input: input.clone(),
+			point,
+			depth,
+		}
+	}
+	fn update_cursors(&mut self) {
+		let (Some(cursor), Some(cursor_lines)) = (&self.settings.cursor, &self.cursor_lines) else {
+			return;
 		};
+		let hover_points = self
+			.hovering
+			.values()
+			.filter(|point| point.z.abs() <= cursor.max_hover_distance)
+			.map(|point| (*point, false));
+		let contact_points = self.contact_data.values().map(|(_, point)| (*point, true));
+		let points = hover_points.chain(contact_points).collect::<Vec<_>>();
+		if self.cursor_points.as_ref() == Some(&points) {
+			return;
+		}
 
-		let x = interact_point
-			.x
-			.clamp(-self.size.x / 2.0, self.size.x / 2.0)
-			.map_range(-self.size.x / 2.0..self.size.x / 2.0, self.x_range.clone());
-		let y = interact_point
-			.y
-			.clamp(-self.size.y / 2.0, self.size.y / 2.0)
-			.map_range(self.size.y / 2.0..-self.size.y / 2.0, self.y_range.clone());
+		let cursors = points
+			.iter()
+			.map(|(point, touching)| {
+				let line = if *touching {
+					// a ring as thick as it is wide looks like a filled dot
+					lines::circle(Self::CURSOR_SEGMENTS, 0.0, cursor.dot_radius * 0.5)
+						.thickness(cursor.dot_radius)
+						.color(cursor.touch_color)
+				} else {
+					let radius = point.z.abs().map_range(
+						0.0..cursor.max_hover_distance,
+						cursor.dot_radius..cursor.ring_radius,
+					);
+					lines::circle(Self::CURSOR_SEGMENTS, 0.0, radius)
+						.thickness(cursor.line_thickness)
+						.color(cursor.hover_color)
+				};
+				let on_plane = self.geometry.clamp(*point).extend(0.0);
+				self.geometry
+					.curve_line(line.transform(Mat4::from_translation(on_plane)))
+			})
+			.collect::<Vec<_>>();
+		let _ = cursor_lines.set_lines(&cursors);
+		self.cursor_points.replace(points);
+	}
+	const CURSOR_SEGMENTS: usize = 16;
 
-		([x, y].into(), interact_point.z)
+	/// Interact point relative to the flat (unrolled) plane, before mapping to the ranges
+	fn interact_point_flat(&self, input: &InputData) -> Vector3<f32> {
+		match &input.input {
+			InputDataType::Pointer(p) => self.geometry.ray_point(p.origin, p.direction()).into(),
+			// hands without any enabled fingers can't touch, but still need somewhere to be
+			InputDataType::Hand(h) => self
+				.fingertip(h)
+				.unwrap_or_else(|| self.flatten(h.index.tip.position)),
+			InputDataType::Tip(t) => self.flatten(t.origin),
+		}
+	}
+	pub fn interact_point(&self, input: &InputData) -> (Vector2<f32>, f32) {
+		self.map_point(self.interact_point_flat(input))
+	}
+	fn map_point(&self, interact_point: Vector3<f32>) -> (Vector2<f32>, f32) {
+		self.geometry
+			.map_point(interact_point, &self.x_range, &self.y_range)
 	}
 	pub fn input_to_points<'a>(
 		&self,
@@ -118,26 +425,61 @@ impl TouchPlane {
 		&self.root
 	}
 	pub fn field(&self) -> &Field {
-		&self.field
+		self.geometry.field()
 	}
 	pub fn action(&self) -> &MultiAction {
 		&self.action
 	}
+	/// Every point touching the plane, with each enabled fingertip on a hand as its own contact.
+	pub fn contacts(&self) -> &DeltaSet<ContactId> {
+		&self.contacts
+	}
+	/// Where a current contact is touching.
+	pub fn contact(&self, id: &ContactId) -> Option<&TouchContact> {
+		self.contact_data.get(id).map(|(contact, _)| contact)
+	}
+	pub fn current_contacts(&self) -> impl Iterator<Item = &TouchContact> {
+		self.contact_data.values().map(|(contact, _)| contact)
+	}
 
-	pub fn set_size(&mut self, size: impl Into<Vector2<f32>>) -> Result<(), NodeError> {
-		let size = size.into();
-		self.size = size;
-		self.field
-			.set_shape(Shape::Box([size.x, size.y, self.thickness].into()))?;
+	pub fn curve(&self) -> Option<CylinderCurve> {
+		self.geometry.curve()
+	}
+	pub fn settings(&self) -> &TouchPlaneSettings {
+		&self.settings
+	}
+	pub fn set_settings(&mut self, settings: TouchPlaneSettings) -> Result<(), NodeError> {
+		match (&settings.cursor, &self.cursor_lines) {
+			(Some(_), None) => {
+				self.cursor_lines
+					.replace(Lines::create(&self.root, Transform::identity(), &[])?);
+			}
+			(None, Some(_)) => {
+				self.cursor_lines.take();
+			}
+			_ => (),
+		}
+		if settings.shape != *self.geometry.shape() {
+			self.geometry.set_shape(settings.shape.clone())?;
+		}
+		self.settings = settings;
+		self.cursor_points = None;
 		Ok(())
 	}
+
+	/// On curved planes the width is along the curve, so this changes the arc.
+	pub fn set_size(&mut self, size: impl Into<Vector2<f32>>) -> Result<(), NodeError> {
+		self.cursor_points = None;
+		self.geometry.set_size(size.into())
+	}
 	pub fn set_thickness(&mut self, thickness: f32) -> Result<(), NodeError> {
-		self.thickness = thickness;
-		self.field
-			.set_local_transform(Transform::from_translation([0.0, 0.0, thickness * -0.5]))?;
-		self.field
-			.set_shape(Shape::Box([self.size.x, self.size.y, thickness].into()))?;
-		Ok(())
+		self.cursor_points = None;
+		self.geometry.set_thickness(thickness)
+	}
+	/// Bend the plane around a cylinder, or flatten it with `None`. This changes the width to match the curve.
+	pub fn set_curve(&mut self, curve: Option<CylinderCurve>) -> Result<(), NodeError> {
+		self.cursor_points = None;
+		self.geometry.set_curve(curve)
 	}
 
 	/// Set whether this will receive input or not
@@ -147,32 +489,38 @@ impl TouchPlane {
 }
 impl VisualDebug for TouchPlane {
 	fn set_debug(&mut self, settings: Option<DebugSettings>) {
-		self.debug_lines = settings.and_then(|settings| {
-			let line_front = lines::rounded_rectangle(
-				self.size.x,
-				self.size.y,
-				settings.line_thickness * 0.5,
-				4,
-			)
-			.thickness(settings.line_thickness)
-			.color(settings.line_color);
-			let line_back = line_front
-				.clone()
-				.color(rgba_linear!(
-					settings.line_color.c.r,
-					settings.line_color.c.g,
-					settings.line_color.c.b,
-					settings.line_color.a * 0.5
-				))
-				.transform(Mat4::from_translation(vec3(0.0, 0.0, -self.thickness)));
-
-			let lines = Lines::create(
-				&self.root,
-				Transform::from_translation([0.0, 0.0, 0.0]),
-				&[line_front, line_back],
-			)
-			.ok()?;
-			Some(lines)
-		})
+		self.geometry.set_debug(&self.root, settings);
 	}
 }
+
+#[test]
+fn touch_plane_hand_contacts() {
+	let finger = |finger| ContactId {
+		input: 0,
+		finger: Some(finger),
+	};
+	let (index, middle) = (finger(Fingertip::Index), finger(Fingertip::Middle));
+	let hovering = |depth: f32| {
+		vec![
+			(index, Vector3::from([0.0, 0.0, depth]), depth <= 0.0),
+			(
+				middle,
+				Vector3::from([0.02, 0.0, depth + 0.01]),
+				depth + 0.01 <= 0.0,
+			),
+		]
+	};
+
+	// hovering in front, then neither hovering nor touching while the hand gets captured
+	let known = [index, middle];
+	assert!(TouchPlane::hand_contacts(&hovering(0.01), false, |id| known.contains(id)).is_empty());
+	// so nothing is known by the time it starts interacting, only the finger that touched counts
+	assert!(TouchPlane::hand_contacts(&hovering(-0.005), false, |_| false).is_empty());
+	let contacts = TouchPlane::hand_contacts(&hovering(-0.005), true, |_| false);
+	assert_eq!(contacts, vec![index]);
+
+	// the middle finger was hovering in front meanwhile, so it counts once it presses in too
+	let known = [index, middle];
+	let contacts = TouchPlane::hand_contacts(&hovering(-0.02), false, |id| known.contains(id));
+	assert_eq!(contacts, vec![index, middle]);
+}